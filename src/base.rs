@@ -1,11 +1,17 @@
-use chrono::{DateTime, SecondsFormat, Utc};
-use quick_xml::Reader;
-use serde::de::Error as DeError;
-use serde::{Deserialize, Deserializer, Serialize, Serializer};
+//! Base schema structure and ser/de.
 
-use crate::Error;
+use chrono::{DateTime, Utc};
+use geographiclib_rs::{DirectGeodesic, Geodesic, InverseGeodesic};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_path_to_error::Segment;
 
-/// Base schema structure and ser/de.
+use crate::coords::{self, Utm};
+use crate::magnetic;
+use crate::time::{self, TimeParsing, Timestamp};
+use crate::{Error, Position};
 
 // See References section in README.md
 pub const COT_BASE_EXAMPLE: &str = r#"
@@ -32,24 +38,12 @@ pub struct Cot<D> {
     pub uid: String,
     #[serde(rename = "@type")]
     pub cot_type: String,
-    #[serde(
-        rename = "@time",
-        serialize_with = "serialize_date",
-        deserialize_with = "deserialize_date"
-    )]
-    pub time: DateTime<Utc>,
-    #[serde(
-        rename = "@start",
-        serialize_with = "serialize_date",
-        deserialize_with = "deserialize_date"
-    )]
-    pub start: DateTime<Utc>,
-    #[serde(
-        rename = "@stale",
-        serialize_with = "serialize_date",
-        deserialize_with = "deserialize_date"
-    )]
-    pub stale: DateTime<Utc>,
+    #[serde(rename = "@time")]
+    pub time: Timestamp,
+    #[serde(rename = "@start")]
+    pub start: Timestamp,
+    #[serde(rename = "@stale")]
+    pub stale: Timestamp,
     #[serde(rename = "@how", skip_serializing_if = "Option::is_none")]
     pub how: Option<String>,
     #[serde(rename = "detail")]
@@ -62,7 +56,9 @@ pub struct Cot<D> {
 pub fn parse_cot_msg_type(text: &str) -> Result<String, Error> {
     match xml_first_element_w_attr(text, "event", "type") {
         Ok(Some(val)) => Ok(val),
-        _ => Err(Error::BadField("No element 'event' with attribute 'type'")),
+        _ => Err(Error::BadField(
+            "No element 'event' with attribute 'type'".into(),
+        )),
     }
 }
 
@@ -77,13 +73,13 @@ pub fn xml_first_element_w_attr(
     loop {
         match reader.read_event()? {
             // Parse attribute `type` in the `event` element.
-            quick_xml::events::Event::Start(ref e) => {
-                if e.name().into_inner() == elt_name.as_bytes() {
-                    for attr in e.attributes() {
-                        let attr = attr?;
-                        if attr.key.into_inner() == attr_name.as_bytes() {
-                            return Ok(Some(String::from_utf8_lossy(&attr.value).to_string()));
-                        }
+            quick_xml::events::Event::Start(ref e)
+                if e.name().into_inner() == elt_name.as_bytes() =>
+            {
+                for attr in e.attributes() {
+                    let attr = attr?;
+                    if attr.key.into_inner() == attr_name.as_bytes() {
+                        return Ok(Some(String::from_utf8_lossy(&attr.value).to_string()));
                     }
                 }
             }
@@ -94,26 +90,208 @@ pub fn xml_first_element_w_attr(
     Ok(None)
 }
 
-pub(crate) fn serialize_date<S>(date: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error>
-where
-    S: Serializer,
-{
-    let s = date.to_rfc3339_opts(SecondsFormat::Millis, true /* use Z for +00:00 */);
-    serializer.serialize_str(&s)
+/// Deserialize XML text, e.g. a [`Cot`] message, like `quick_xml::de::from_str()`, but return
+/// errors as [`Error::Located`], with the path and position of the element or attribute which
+/// failed and its value:
+///
+/// ```rust
+/// # use cot_proto::base::{from_str, CotBase};
+/// # use cot_proto::examples::COT_TRACK_EXAMPLE;
+/// let input = COT_TRACK_EXAMPLE.replace("lat=\"-23.14187321890312\"", "lat=\"S23\"");
+/// let e = from_str::<CotBase>(&input).unwrap_err();
+/// assert_eq!(e.path(), Some("event/point/@lat"));
+/// assert_eq!(e.position().unwrap().line, 4);
+/// ```
+pub fn from_str<T: DeserializeOwned>(input: &str) -> Result<T, Error> {
+    let mut deserializer = quick_xml::de::Deserializer::from_str(input);
+    serde_path_to_error::deserialize(&mut deserializer).map_err(|e| {
+        let path = e.path().clone();
+        let source = Error::De(e.into_inner());
+        if let Error::De(quick_xml::DeError::InvalidXml(_)) = source {
+            return locate_error(input, source);
+        }
+        locate_path(input, &path, source)
+    })
+}
+
+/// [`from_str()`], parsing timestamps according to `time_parsing` rather than strictly:
+///
+/// ```rust
+/// # use cot_proto::base::{from_str_with, CotBase, COT_BASE_EXAMPLE};
+/// # use cot_proto::time::TimeParsing;
+/// let input = COT_BASE_EXAMPLE.replace("2005-04-05T11:43:38.07Z", "2005-04-05 11:43:38.07");
+/// let cot: CotBase = from_str_with(&input, TimeParsing::Lenient).unwrap();
+/// // Written as received
+/// assert!(quick_xml::se::to_string(&cot).unwrap().contains("time=\"2005-04-05 11:43:38.07\""));
+/// ```
+pub fn from_str_with<T: DeserializeOwned>(
+    input: &str,
+    time_parsing: TimeParsing,
+) -> Result<T, Error> {
+    time::with_parsing(time_parsing, || from_str(input))
 }
 
-pub(crate) fn deserialize_date<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    let s = String::deserialize(deserializer)?;
-    DateTime::parse_from_rfc3339(&s)
-        .map_err(DeError::custom)
-        .map(|dt| dt.with_timezone(&Utc))
+/// Add the position and element path of the first XML syntax error in `input` to `error`, if it
+/// isn't [`Error::Located`] already.
+pub fn locate_error(input: &str, error: Error) -> Error {
+    if let Error::Located { .. } = error {
+        return error;
+    }
+    let mut reader = Reader::from_str(input);
+    let mut path: Vec<String> = vec![];
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => path.push(String::from_utf8_lossy(e.name().as_ref()).into()),
+            Ok(Event::End(_)) => {
+                path.pop();
+            }
+            Ok(Event::Eof) => return error,
+            Ok(_) => {}
+            Err(_) => {
+                return Error::Located {
+                    path: path.join("/"),
+                    position: Some(Position::new(input, reader.error_position() as usize)),
+                    value: None,
+                    source: Box::new(error),
+                }
+            }
+        }
+    }
+}
+
+/// Locate the element or attribute at the serde `path` in `input`, for errors from
+/// [`from_str()`].
+fn locate_path(input: &str, path: &serde_path_to_error::Path, source: Error) -> Error {
+    // Steps of (element name, index among siblings of that name), then the attribute or text.
+    let mut steps: Vec<(String, usize)> = vec![];
+    let mut leaf = None;
+    for segment in path.iter() {
+        match segment {
+            Segment::Map { key } if key.starts_with('@') || key == "$text" => {
+                leaf = Some(key.clone());
+            }
+            Segment::Map { key } if key == "$value" => {}
+            Segment::Map { key } | Segment::Enum { variant: key } => steps.push((key.clone(), 0)),
+            Segment::Seq { index } => {
+                if let Some(step) = steps.last_mut() {
+                    step.1 = *index;
+                }
+            }
+            Segment::Unknown => {}
+        }
+    }
+
+    let mut reader = Reader::from_str(input);
+    let mut root = None;
+    let mut found = None;
+    let mut matched = 0;
+    let mut depth = 0;
+    let mut count = 0;
+    loop {
+        let offset = reader.buffer_position() as usize;
+        let (start, is_empty) = match reader.read_event() {
+            Ok(Event::Start(e)) => (e, false),
+            Ok(Event::Empty(e)) => (e, true),
+            Ok(Event::End(_)) => {
+                depth -= 1;
+                if depth <= matched {
+                    break;
+                }
+                continue;
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            Ok(_) => continue,
+        };
+        if root.is_none() {
+            root = Some(String::from_utf8_lossy(start.name().as_ref()).to_string());
+            found = Some((offset, start.into_owned()));
+            if is_empty {
+                break;
+            }
+            depth = 1;
+            continue;
+        }
+        if depth == matched + 1 && matched < steps.len() {
+            let (name, index) = &steps[matched];
+            if start.name().as_ref() == name.as_bytes() {
+                if count == *index {
+                    found = Some((offset, start.clone().into_owned()));
+                    matched += 1;
+                    count = 0;
+                    if matched == steps.len() || is_empty {
+                        break;
+                    }
+                } else {
+                    count += 1;
+                }
+            }
+        }
+        if !is_empty {
+            depth += 1;
+        }
+    }
+
+    let mut path_str = root.unwrap_or_default();
+    for (name, index) in &steps {
+        path_str += &format!("/{}", name);
+        if *index > 0 {
+            path_str += &format!("[{}]", index + 1);
+        }
+    }
+    let mut position = found.as_ref().map(|(offset, _)| *offset);
+    let mut value = None;
+    match leaf.as_deref() {
+        Some("$text") => path_str += "/text()",
+        Some(attr) => {
+            path_str += &format!("/{}", attr);
+            if let Some((offset, start)) = &found {
+                if let Some((attr_offset, attr_value)) =
+                    find_attr(input, *offset, start, &attr[1..])
+                {
+                    position = Some(attr_offset);
+                    value = Some(attr_value);
+                }
+            }
+        }
+        None => {}
+    }
+    Error::Located {
+        path: path_str,
+        position: position.map(|offset| Position::new(input, offset)),
+        value,
+        source: Box::new(source),
+    }
+}
+
+/// Offset and value of attribute `name` of the tag `start` at `offset` in `input`.
+pub(crate) fn find_attr(
+    input: &str,
+    offset: usize,
+    start: &BytesStart,
+    name: &str,
+) -> Option<(usize, String)> {
+    let value = start
+        .attributes()
+        .flatten()
+        .find(|a| a.key.as_ref() == name.as_bytes())?
+        .unescape_value()
+        .ok()?
+        .to_string();
+    let tag = &input[offset..];
+    let tag = &tag[..tag.find('>').unwrap_or(tag.len())];
+    let attr_offset = tag.match_indices(name).find_map(|(i, _)| {
+        let before = tag[..i].chars().next_back()?;
+        let after = tag[i + name.len()..].trim_start();
+        (before.is_whitespace() && after.starts_with('=')).then_some(offset + i)
+    })?;
+    Some((attr_offset, value))
 }
 
 pub type CotBase = Cot<NoDetail>;
 
+/// Value used by TAK for unknown `hae`, `ce` and `le` values in a [`Point`].
+pub const UNKNOWN_VALUE: f32 = 9_999_999.0;
+
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
 pub struct NoDetail {}
 
@@ -141,10 +319,194 @@ impl Point {
             le: 0.0,
         }
     }
+
+    /// Create a point at the given coordinates, with unknown altitude and error values.
+    pub fn from_lat_lon(lat: f64, lon: f64) -> Self {
+        Self {
+            lat,
+            lon,
+            ce: UNKNOWN_VALUE,
+            hae: UNKNOWN_VALUE,
+            le: UNKNOWN_VALUE,
+        }
+    }
+
+    /// Geodesic distance in meters to `other` on the WGS-84 ellipsoid, ignoring altitude.
+    pub fn distance_to(&self, other: &Point) -> f64 {
+        Geodesic::wgs84().inverse(self.lat, self.lon, other.lat, other.lon)
+    }
+
+    /// Initial bearing of the geodesic to `other`, in degrees clockwise from true north
+    /// `[0, 360)`.
+    pub fn bearing_to(&self, other: &Point) -> f64 {
+        let (azi1, _azi2, _a12): (f64, f64, f64) =
+            Geodesic::wgs84().inverse(self.lat, self.lon, other.lat, other.lon);
+        normalize_bearing(azi1)
+    }
+
+    /// Final bearing of the geodesic to `other`, i.e. the direction of travel on arrival, in
+    /// degrees clockwise from true north `[0, 360)`.
+    pub fn final_bearing_to(&self, other: &Point) -> f64 {
+        let (_azi1, azi2, _a12): (f64, f64, f64) =
+            Geodesic::wgs84().inverse(self.lat, self.lon, other.lat, other.lon);
+        normalize_bearing(azi2)
+    }
+
+    /// Point reached by travelling `distance` meters from this point along the geodesic with
+    /// initial `bearing` in degrees from true north. Altitude and error values are copied.
+    pub fn destination(&self, bearing: f64, distance: f64) -> Point {
+        let (lat, lon): (f64, f64) =
+            Geodesic::wgs84().direct(self.lat, self.lon, bearing, distance);
+        Point {
+            lat,
+            lon,
+            ..self.clone()
+        }
+    }
+
+    /// Point halfway along the geodesic to `other`. Altitude and error values are copied from
+    /// this point.
+    pub fn midpoint(&self, other: &Point) -> Point {
+        let geodesic = Geodesic::wgs84();
+        let (s12, azi1, _azi2, _a12): (f64, f64, f64, f64) =
+            geodesic.inverse(self.lat, self.lon, other.lat, other.lon);
+        let (lat, lon): (f64, f64) = geodesic.direct(self.lat, self.lon, azi1, s12 / 2.0);
+        Point {
+            lat,
+            lon,
+            ..self.clone()
+        }
+    }
+
+    /// Vertices of an ellipse centered on this point, e.g. to draw a TAK `<ellipse>`.
+    ///
+    /// `major` and `minor` are the semi-axes in meters and `angle` the bearing of the major axis
+    /// in degrees from true north. Returns `segments` points clockwise from the end of the major
+    /// axis, without repeating the first point.
+    pub fn ellipse_polygon(
+        &self,
+        major: f64,
+        minor: f64,
+        angle: f64,
+        segments: usize,
+    ) -> Vec<Point> {
+        (0..segments)
+            .map(|i| {
+                let theta = 360.0 * i as f64 / segments as f64;
+                let (sin, cos) = theta.to_radians().sin_cos();
+                let radius = major * minor / ((minor * cos).powi(2) + (major * sin).powi(2)).sqrt();
+                self.destination(angle + theta, radius)
+            })
+            .collect()
+    }
+
+    /// MGRS reference with `precision` digits per coordinate, e.g. `18S UJ 23487 06483` for 5
+    /// (1 m). See [`coords::to_mgrs()`].
+    pub fn to_mgrs(&self, precision: usize) -> Result<String, Error> {
+        coords::to_mgrs(self.lat, self.lon, precision)
+    }
+
+    /// Point at the center of an MGRS square, with unknown altitude and error values.
+    pub fn from_mgrs(mgrs: &str) -> Result<Self, Error> {
+        let (lat, lon) = coords::from_mgrs(mgrs)?;
+        Ok(Self::from_lat_lon(lat, lon))
+    }
+
+    /// UTM coordinate, or UPS in the polar regions.
+    pub fn to_utm(&self) -> Result<Utm, Error> {
+        Utm::from_lat_lon(self.lat, self.lon)
+    }
+
+    pub fn from_utm(utm: &Utm) -> Result<Self, Error> {
+        let (lat, lon) = utm.to_lat_lon()?;
+        Ok(Self::from_lat_lon(lat, lon))
+    }
+
+    /// Position in degrees, minutes and seconds, e.g. `38°51'23.94"N 77°03'49.11"W`.
+    pub fn to_dms(&self) -> String {
+        coords::format_dms(self.lat, self.lon)
+    }
+
+    /// Position in degrees and decimal minutes, e.g. `38°51.3990'N 77°03.8185'W`.
+    pub fn to_ddm(&self) -> String {
+        coords::format_ddm(self.lat, self.lon)
+    }
+
+    /// Parse user-entered coordinates: latitude/longitude in any form accepted by
+    /// [`coords::parse_lat_lon()`], MGRS, or UTM/UPS.
+    pub fn parse_coordinates(s: &str) -> Result<Self, Error> {
+        if let Ok((lat, lon)) = coords::parse_lat_lon(s) {
+            return Ok(Self::from_lat_lon(lat, lon));
+        }
+        if let Ok(point) = Self::from_mgrs(s) {
+            return Ok(point);
+        }
+        match s.parse::<Utm>() {
+            Ok(utm) => Self::from_utm(&utm),
+            Err(_) => Err(Error::BadField("unrecognized coordinate format".into())),
+        }
+    }
+
+    /// Height above mean sea level in meters, using the geoid returned by
+    /// [`geoid::default_geoid()`](crate::geoid::default_geoid). `None` if `hae` is unknown.
+    #[cfg(feature = "geoid")]
+    pub fn msl(&self) -> Option<f32> {
+        if self.hae == UNKNOWN_VALUE {
+            return None;
+        }
+        let geoid = crate::geoid::default_geoid();
+        Some(geoid.hae_to_msl(self.lat, self.lon, self.hae as f64) as f32)
+    }
+
+    /// This point with `hae` set from a height above mean sea level, using the geoid returned by
+    /// [`geoid::default_geoid()`](crate::geoid::default_geoid).
+    #[cfg(feature = "geoid")]
+    pub fn with_msl(&self, msl: f32) -> Self {
+        let geoid = crate::geoid::default_geoid();
+        Self {
+            hae: geoid.msl_to_hae(self.lat, self.lon, msl as f64) as f32,
+            ..self.clone()
+        }
+    }
+
+    /// Magnetic declination in degrees at this point at `time`, positive east, from the World
+    /// Magnetic Model returned by [`magnetic::model_for()`].
+    pub fn declination(&self, time: &DateTime<Utc>) -> f64 {
+        let height = if self.hae == UNKNOWN_VALUE {
+            0.0
+        } else {
+            self.hae as f64
+        };
+        let year = magnetic::decimal_year(time);
+        magnetic::model_for(year).declination(self.lat, self.lon, height, year)
+    }
+
+    /// UTM/UPS grid convergence in degrees at this point. See [`coords::grid_convergence()`].
+    pub fn grid_convergence(&self) -> Result<f64, Error> {
+        coords::grid_convergence(self.lat, self.lon)
+    }
+
+    /// Vertices of a circle of `radius` meters centered on this point. See
+    /// [`Point::ellipse_polygon()`].
+    pub fn circle_polygon(&self, radius: f64, segments: usize) -> Vec<Point> {
+        self.ellipse_polygon(radius, radius, 0.0, segments)
+    }
+}
+
+fn normalize_bearing(azimuth: f64) -> f64 {
+    let bearing = azimuth.rem_euclid(360.0);
+    // rem_euclid can round up to exactly 360 for tiny negative values
+    if bearing >= 360.0 {
+        0.0
+    } else {
+        bearing
+    }
 }
 
 #[cfg(test)]
 mod test {
+    use crate::examples::COT_TRACK_EXAMPLE;
+
     use super::*;
     #[test]
     fn test_serde_roundtrip() {
@@ -155,4 +517,138 @@ mod test {
         let cot1: CotBase = quick_xml::de::from_str(&cot_str).unwrap();
         assert_eq!(cot0, cot1);
     }
+
+    #[test]
+    fn test_timestamps_relayed_unchanged() {
+        use crate::examples::COT_STRIKE_EXAMPLE;
+
+        let cot: CotBase = from_str(COT_STRIKE_EXAMPLE).unwrap();
+        let cot_str = quick_xml::se::to_string(&cot).unwrap();
+        assert!(cot_str.contains(
+            r#"time="2023-10-24T03:16:14.897441Z" start="2023-10-24T03:16:15.897441Z" stale="2023-10-24T03:18:14.897441Z""#
+        ));
+
+        let input = COT_BASE_EXAMPLE.replace("38.07Z", "38.07+0000");
+        assert!(from_str::<CotBase>(&input).is_err());
+        let cot: CotBase = from_str_with(&input, TimeParsing::Lenient).unwrap();
+        let base: CotBase = from_str(COT_BASE_EXAMPLE).unwrap();
+        assert_eq!(cot.time, base.time);
+        let cot_str = quick_xml::se::to_string(&cot).unwrap();
+        assert!(cot_str.contains(
+            r#"time="2005-04-05T11:43:38.07+0000" start="2005-04-05T11:43:38.07+0000" stale="2005-04-05T11:45:38.07+0000""#
+        ));
+    }
+
+    #[test]
+    fn test_error_location() {
+        #[derive(Debug, Deserialize)]
+        struct Contact {
+            #[serde(rename = "@phone")]
+            _phone: u64,
+        }
+        #[derive(Debug, Deserialize)]
+        struct Link {
+            #[serde(rename = "@uid")]
+            _uid: u32,
+        }
+        #[derive(Debug, Deserialize)]
+        struct TestDetail {
+            #[serde(rename = "contact")]
+            _contact: Contact,
+            #[serde(rename = "link", default)]
+            _links: Vec<Link>,
+        }
+
+        let input = COT_TRACK_EXAMPLE.replace(
+            r#"<contact callsign="BLAMO-IDM1-3V"/>"#,
+            r#"<contact callsign="BLAMO-IDM1-3V" phone="555-1234"/>"#,
+        );
+        let e = from_str::<Cot<TestDetail>>(&input).unwrap_err();
+        assert_eq!(e.path(), Some("event/detail/contact/@phone"));
+        let position = e.position().unwrap();
+        assert_eq!((position.line, position.column), (6, 35));
+        assert_eq!(&input[position.offset..][..6], "phone=");
+        assert!(matches!(e.root_cause(), Error::De(_)));
+        let message = e.to_string();
+        assert!(
+            message.starts_with(
+                r#"event/detail/contact/@phone at line 6, column 35 (value "555-1234"): "#
+            ),
+            "{}",
+            message
+        );
+
+        let input = COT_TRACK_EXAMPLE.replace(
+            r#"<contact callsign="BLAMO-IDM1-3V"/>"#,
+            r#"<contact phone="1"/><link uid="1"/>
+<link uid="two"/>"#,
+        );
+        let e = from_str::<Cot<TestDetail>>(&input).unwrap_err();
+        assert_eq!(e.path(), Some("event/detail/link[2]/@uid"));
+        assert_eq!(e.position().unwrap().line, 7);
+
+        // XML syntax errors
+        let input = COT_TRACK_EXAMPLE.replace("</detail>", "</details>");
+        let e = from_str::<CotBase>(&input).unwrap_err();
+        assert_eq!(e.path(), Some("event/detail"));
+        assert_eq!(e.position().unwrap().line, 8);
+    }
+
+    #[test]
+    fn test_point_distance() {
+        // One degree of latitude at the equator on WGS-84.
+        let p0 = Point::from_lat_lon(0.0, 0.0);
+        let p1 = Point::from_lat_lon(1.0, 0.0);
+        assert!((p0.distance_to(&p1) - 110_574.389).abs() < 1e-3);
+        assert_eq!(p0.distance_to(&p0), 0.0);
+        // Nearly antipodal points, where Vincenty's method fails to converge.
+        let p2 = Point::from_lat_lon(0.5, 179.5);
+        assert!((p0.distance_to(&p2) - 19_936_288.579).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_point_bearings() {
+        // Vincenty's (1975) example: Flinders Peak to Buninyong.
+        let dms = |d: f64, m: f64, s: f64| d.signum() * (d.abs() + m / 60.0 + s / 3600.0);
+        let flinders = Point::from_lat_lon(dms(-37.0, 57.0, 3.72030), dms(144.0, 25.0, 29.52440));
+        let buninyong = Point::from_lat_lon(dms(-37.0, 39.0, 10.15610), dms(143.0, 55.0, 35.38390));
+        assert!((flinders.distance_to(&buninyong) - 54_972.271).abs() < 1e-3);
+        let initial = dms(306.0, 52.0, 5.37);
+        assert!((flinders.bearing_to(&buninyong) - initial).abs() < 1e-5);
+        let final_ = dms(307.0, 10.0, 25.07);
+        assert!((flinders.final_bearing_to(&buninyong) - final_).abs() < 1e-5);
+
+        let mid = flinders.midpoint(&buninyong);
+        let half = flinders.distance_to(&buninyong) / 2.0;
+        assert!((flinders.distance_to(&mid) - half).abs() < 1e-6);
+        assert!((mid.distance_to(&buninyong) - half).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_point_destination() {
+        let p0 = Point::from_lat_lon(38.0, -77.0);
+        assert!((p0.bearing_to(&Point::from_lat_lon(39.0, -77.0)) - 0.0).abs() < 1e-9);
+        // Due west along a parallel, the great circle starts slightly north of west.
+        let west = p0.bearing_to(&Point::from_lat_lon(38.0, -78.0));
+        assert!(west > 270.0 && west < 271.0, "{}", west);
+        let p1 = p0.destination(45.0, 1000.0);
+        assert!((p0.distance_to(&p1) - 1000.0).abs() < 1e-6);
+        assert!((p0.bearing_to(&p1) - 45.0).abs() < 1e-6);
+        let p2 = Point::from_lat_lon(10.0, 179.9).destination(90.0, 50_000.0);
+        assert!(p2.lon < -179.0);
+    }
+
+    #[test]
+    fn test_ellipse_polygon() {
+        let center = Point::from_lat_lon(38.0, -77.0);
+        let vertices = center.ellipse_polygon(1000.0, 500.0, 90.0, 8);
+        assert_eq!(vertices.len(), 8);
+        assert!((center.distance_to(&vertices[0]) - 1000.0).abs() < 1e-6);
+        assert!((center.bearing_to(&vertices[0]) - 90.0).abs() < 1e-6);
+        assert!((center.distance_to(&vertices[2]) - 500.0).abs() < 1e-6);
+        assert!((center.bearing_to(&vertices[2]) - 180.0).abs() < 1e-6);
+        for v in center.circle_polygon(250.0, 36) {
+            assert!((center.distance_to(&v) - 250.0).abs() < 1e-6);
+        }
+    }
 }
@@ -0,0 +1,858 @@
+//! Coordinate conversions between WGS-84 latitude/longitude and UTM/UPS, MGRS, and
+//! degrees-minutes-seconds (DMS) or degrees-decimal-minutes (DDM) strings.
+//!
+//! ```rust
+//! # use cot_proto::base::Point;
+//! let eiffel = Point::from_lat_lon(48.8582, 2.2945);
+//! assert_eq!(eiffel.to_mgrs(5).unwrap(), "31U DQ 48251 11932");
+//! assert_eq!(eiffel.to_utm().unwrap().to_string(), "31N 448252 5411933");
+//! assert_eq!(eiffel.to_dms(), "48°51'29.52\"N 2°17'40.20\"E");
+//!
+//! let p = Point::parse_coordinates("31U DQ 48251 11932").unwrap();
+//! assert!(p.distance_to(&eiffel) < 2.0);
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::Error;
+
+const WGS84_A: f64 = 6_378_137.0;
+const WGS84_F: f64 = 1.0 / 298.257_223_563;
+
+const UTM_K0: f64 = 0.9996;
+const UTM_FALSE_EASTING: f64 = 500_000.0;
+const UTM_FALSE_NORTHING_SOUTH: f64 = 10_000_000.0;
+/// Latitude limits of the UTM system; UPS is used outside them.
+const UTM_MIN_LAT: f64 = -80.0;
+const UTM_MAX_LAT: f64 = 84.0;
+
+const UPS_K0: f64 = 0.994;
+const UPS_FALSE_ORIGIN: f64 = 2_000_000.0;
+
+/// MGRS latitude bands, 8° each from 80°S, with X extended to 84°N.
+const MGRS_LAT_BANDS: &[u8] = b"CDEFGHJKLMNPQRSTUVWXX";
+/// MGRS 100 km square column letters, for zones 1, 2, 3 (mod 3).
+const MGRS_COL_LETTERS: [&[u8]; 3] = [b"ABCDEFGH", b"JKLMNPQR", b"STUVWXYZ"];
+/// MGRS 100 km square row letters; even zones start 5 letters in.
+const MGRS_ROW_LETTERS: &[u8] = b"ABCDEFGHJKLMNPQRSTUV";
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Hemisphere {
+    North,
+    South,
+}
+
+impl Hemisphere {
+    fn letter(self) -> char {
+        match self {
+            Hemisphere::North => 'N',
+            Hemisphere::South => 'S',
+        }
+    }
+}
+
+/// UTM coordinate, or UPS coordinate for the polar regions when `zone` is 0.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Utm {
+    /// UTM zone `1..=60`, or 0 for UPS.
+    pub zone: u8,
+    pub hemisphere: Hemisphere,
+    /// Easting in meters, including the false easting.
+    pub easting: f64,
+    /// Northing in meters, including any false northing.
+    pub northing: f64,
+}
+
+/// Constants of the Krüger series for the transverse Mercator projection, to order n⁶ (Karney
+/// 2011), which are accurate to a few nanometers within UTM zones.
+struct TransverseMercator {
+    e: f64,
+    /// Rectifying radius.
+    a: f64,
+    alpha: [f64; 6],
+    beta: [f64; 6],
+}
+
+impl TransverseMercator {
+    fn wgs84() -> Self {
+        let f = WGS84_F;
+        let e = (f * (2.0 - f)).sqrt();
+        let n = f / (2.0 - f);
+        let (n2, n3, n4, n5, n6) = (n * n, n.powi(3), n.powi(4), n.powi(5), n.powi(6));
+        let a = WGS84_A / (1.0 + n) * (1.0 + n2 / 4.0 + n4 / 64.0 + n6 / 256.0);
+        let alpha = [
+            n / 2.0 - 2.0 / 3.0 * n2 + 5.0 / 16.0 * n3 + 41.0 / 180.0 * n4 - 127.0 / 288.0 * n5
+                + 7891.0 / 37800.0 * n6,
+            13.0 / 48.0 * n2 - 3.0 / 5.0 * n3 + 557.0 / 1440.0 * n4 + 281.0 / 630.0 * n5
+                - 1983433.0 / 1935360.0 * n6,
+            61.0 / 240.0 * n3 - 103.0 / 140.0 * n4
+                + 15061.0 / 26880.0 * n5
+                + 167603.0 / 181440.0 * n6,
+            49561.0 / 161280.0 * n4 - 179.0 / 168.0 * n5 + 6601661.0 / 7257600.0 * n6,
+            34729.0 / 80640.0 * n5 - 3418889.0 / 1995840.0 * n6,
+            212378941.0 / 319334400.0 * n6,
+        ];
+        let beta = [
+            n / 2.0 - 2.0 / 3.0 * n2 + 37.0 / 96.0 * n3 - 1.0 / 360.0 * n4 - 81.0 / 512.0 * n5
+                + 96199.0 / 604800.0 * n6,
+            1.0 / 48.0 * n2 + 1.0 / 15.0 * n3 - 437.0 / 1440.0 * n4 + 46.0 / 105.0 * n5
+                - 1118711.0 / 3870720.0 * n6,
+            17.0 / 480.0 * n3 - 37.0 / 840.0 * n4 - 209.0 / 4480.0 * n5 + 5569.0 / 90720.0 * n6,
+            4397.0 / 161280.0 * n4 - 11.0 / 504.0 * n5 - 830251.0 / 7257600.0 * n6,
+            4583.0 / 161280.0 * n5 - 108847.0 / 3991680.0 * n6,
+            20648693.0 / 638668800.0 * n6,
+        ];
+        Self { e, a, alpha, beta }
+    }
+
+    /// Project latitude and longitude relative to the central meridian (degrees) to `(x, y)` in
+    /// meters, scaled by `k0` but without false easting/northing.
+    fn forward(&self, lat: f64, dlon: f64, k0: f64) -> (f64, f64) {
+        let e = self.e;
+        let tau = lat.to_radians().tan();
+        let sigma = (e * (e * tau / (1.0 + tau * tau).sqrt()).atanh()).sinh();
+        let tau_p = tau * (1.0 + sigma * sigma).sqrt() - sigma * (1.0 + tau * tau).sqrt();
+        let (sin_l, cos_l) = dlon.to_radians().sin_cos();
+        let xi_p = tau_p.atan2(cos_l);
+        let eta_p = (sin_l / (tau_p * tau_p + cos_l * cos_l).sqrt()).asinh();
+        let (mut xi, mut eta) = (xi_p, eta_p);
+        for (j, alpha) in self.alpha.iter().enumerate() {
+            let k = 2.0 * (j + 1) as f64;
+            xi += alpha * (k * xi_p).sin() * (k * eta_p).cosh();
+            eta += alpha * (k * xi_p).cos() * (k * eta_p).sinh();
+        }
+        (k0 * self.a * eta, k0 * self.a * xi)
+    }
+
+    /// Meridian convergence in degrees at latitude and longitude relative to the central meridian:
+    /// the bearing of grid north from true north.
+    fn convergence(&self, lat: f64, dlon: f64) -> f64 {
+        let e = self.e;
+        let tau = lat.to_radians().tan();
+        let sigma = (e * (e * tau / (1.0 + tau * tau).sqrt()).atanh()).sinh();
+        let tau_p = tau * (1.0 + sigma * sigma).sqrt() - sigma * (1.0 + tau * tau).sqrt();
+        let (sin_l, cos_l) = dlon.to_radians().sin_cos();
+        let xi_p = tau_p.atan2(cos_l);
+        let eta_p = (sin_l / (tau_p * tau_p + cos_l * cos_l).sqrt()).asinh();
+        let gamma_p = (tau_p * sin_l).atan2(cos_l * (1.0 + tau_p * tau_p).sqrt());
+        let (mut p, mut q) = (1.0, 0.0);
+        for (j, alpha) in self.alpha.iter().enumerate() {
+            let k = 2.0 * (j + 1) as f64;
+            p += k * alpha * (k * xi_p).cos() * (k * eta_p).cosh();
+            q += k * alpha * (k * xi_p).sin() * (k * eta_p).sinh();
+        }
+        (gamma_p + q.atan2(p)).to_degrees()
+    }
+
+    /// Inverse of [`TransverseMercator::forward()`], returning latitude and longitude relative to
+    /// the central meridian in degrees.
+    fn inverse(&self, x: f64, y: f64, k0: f64) -> (f64, f64) {
+        let e = self.e;
+        let eta = x / (k0 * self.a);
+        let xi = y / (k0 * self.a);
+        let (mut xi_p, mut eta_p) = (xi, eta);
+        for (j, beta) in self.beta.iter().enumerate() {
+            let k = 2.0 * (j + 1) as f64;
+            xi_p -= beta * (k * xi).sin() * (k * eta).cosh();
+            eta_p -= beta * (k * xi).cos() * (k * eta).sinh();
+        }
+        let sinh_eta_p = eta_p.sinh();
+        let (sin_xi_p, cos_xi_p) = xi_p.sin_cos();
+        let tau_p = sin_xi_p / (sinh_eta_p * sinh_eta_p + cos_xi_p * cos_xi_p).sqrt();
+        // Newton-Raphson iteration for tau from tau'
+        let mut tau = tau_p;
+        for _ in 0..10 {
+            let sigma = (e * (e * tau / (1.0 + tau * tau).sqrt()).atanh()).sinh();
+            let tau_i = tau * (1.0 + sigma * sigma).sqrt() - sigma * (1.0 + tau * tau).sqrt();
+            let delta = (tau_p - tau_i) / (1.0 + tau_i * tau_i).sqrt()
+                * (1.0 + (1.0 - e * e) * tau * tau)
+                / ((1.0 - e * e) * (1.0 + tau * tau).sqrt());
+            tau += delta;
+            if delta.abs() < 1e-12 {
+                break;
+            }
+        }
+        let lat = tau.atan().to_degrees();
+        let dlon = sinh_eta_p.atan2(cos_xi_p).to_degrees();
+        (lat, dlon)
+    }
+}
+
+fn check_lat_lon(lat: f64, lon: f64) -> Result<(), Error> {
+    if !(-90.0..=90.0).contains(&lat) || !lon.is_finite() {
+        return Err(Error::BadField("latitude must be within [-90, 90]".into()));
+    }
+    Ok(())
+}
+
+fn normalize_lon(lon: f64) -> f64 {
+    (lon + 540.0).rem_euclid(360.0) - 180.0
+}
+
+/// UTM zone for a position, including the Norway and Svalbard exceptions.
+fn utm_zone(lat: f64, lon: f64) -> u8 {
+    let mut zone = (((lon + 180.0) / 6.0).floor() as i32 + 1).clamp(1, 60) as u8;
+    if (56.0..64.0).contains(&lat) && zone == 31 && lon >= 3.0 {
+        zone = 32;
+    }
+    if lat >= 72.0 {
+        zone = match (zone, lon) {
+            (32, lon) if lon < 9.0 => 31,
+            (32, _) => 33,
+            (34, lon) if lon < 21.0 => 33,
+            (34, _) => 35,
+            (36, lon) if lon < 33.0 => 35,
+            (36, _) => 37,
+            (zone, _) => zone,
+        };
+    }
+    zone
+}
+
+fn central_meridian(zone: u8) -> f64 {
+    zone as f64 * 6.0 - 183.0
+}
+
+impl Utm {
+    /// Convert a position to UTM, or to UPS beyond the UTM latitude limits (80°S and 84°N).
+    pub fn from_lat_lon(lat: f64, lon: f64) -> Result<Self, Error> {
+        check_lat_lon(lat, lon)?;
+        let lon = normalize_lon(lon);
+        let hemisphere = if lat >= 0.0 {
+            Hemisphere::North
+        } else {
+            Hemisphere::South
+        };
+        if !(UTM_MIN_LAT..UTM_MAX_LAT).contains(&lat) {
+            return Ok(Self::ups_from_lat_lon(lat, lon, hemisphere));
+        }
+        let zone = utm_zone(lat, lon);
+        let tm = TransverseMercator::wgs84();
+        let (x, y) = tm.forward(lat, lon - central_meridian(zone), UTM_K0);
+        let northing = match hemisphere {
+            Hemisphere::North => y,
+            Hemisphere::South => y + UTM_FALSE_NORTHING_SOUTH,
+        };
+        Ok(Self {
+            zone,
+            hemisphere,
+            easting: x + UTM_FALSE_EASTING,
+            northing,
+        })
+    }
+
+    fn ups_from_lat_lon(lat: f64, lon: f64, hemisphere: Hemisphere) -> Self {
+        let e = TransverseMercator::wgs84().e;
+        // Work with the absolute latitude, i.e. as if at the north pole.
+        let phi = lat.abs().to_radians();
+        let esin = e * phi.sin();
+        let t = (std::f64::consts::FRAC_PI_4 - phi / 2.0).tan()
+            / ((1.0 - esin) / (1.0 + esin)).powf(e / 2.0);
+        let c = ((1.0 + e).powf(1.0 + e) * (1.0 - e).powf(1.0 - e)).sqrt();
+        let rho = 2.0 * WGS84_A * UPS_K0 * t / c;
+        let (sin_l, cos_l) = lon.to_radians().sin_cos();
+        let northing = match hemisphere {
+            Hemisphere::North => UPS_FALSE_ORIGIN - rho * cos_l,
+            Hemisphere::South => UPS_FALSE_ORIGIN + rho * cos_l,
+        };
+        Self {
+            zone: 0,
+            hemisphere,
+            easting: UPS_FALSE_ORIGIN + rho * sin_l,
+            northing,
+        }
+    }
+
+    pub fn is_ups(&self) -> bool {
+        self.zone == 0
+    }
+
+    /// Convert to latitude and longitude in degrees.
+    pub fn to_lat_lon(&self) -> Result<(f64, f64), Error> {
+        if self.zone > 60 {
+            return Err(Error::BadField("UTM zone must be within 1..=60".into()));
+        }
+        if self.is_ups() {
+            return Ok(self.ups_to_lat_lon());
+        }
+        let x = self.easting - UTM_FALSE_EASTING;
+        let y = match self.hemisphere {
+            Hemisphere::North => self.northing,
+            Hemisphere::South => self.northing - UTM_FALSE_NORTHING_SOUTH,
+        };
+        let (lat, dlon) = TransverseMercator::wgs84().inverse(x, y, UTM_K0);
+        Ok((lat, normalize_lon(central_meridian(self.zone) + dlon)))
+    }
+
+    fn ups_to_lat_lon(&self) -> (f64, f64) {
+        let e = TransverseMercator::wgs84().e;
+        let de = self.easting - UPS_FALSE_ORIGIN;
+        let dn = self.northing - UPS_FALSE_ORIGIN;
+        let rho = de.hypot(dn);
+        let c = ((1.0 + e).powf(1.0 + e) * (1.0 - e).powf(1.0 - e)).sqrt();
+        let t = rho * c / (2.0 * WGS84_A * UPS_K0);
+        let mut phi = std::f64::consts::FRAC_PI_2 - 2.0 * t.atan();
+        for _ in 0..20 {
+            let esin = e * phi.sin();
+            let next = std::f64::consts::FRAC_PI_2
+                - 2.0 * (t * ((1.0 - esin) / (1.0 + esin)).powf(e / 2.0)).atan();
+            let done = (next - phi).abs() < 1e-14;
+            phi = next;
+            if done {
+                break;
+            }
+        }
+        let (lat, lon) = match self.hemisphere {
+            Hemisphere::North => (phi, de.atan2(-dn)),
+            Hemisphere::South => (-phi, de.atan2(dn)),
+        };
+        (lat.to_degrees(), lon.to_degrees())
+    }
+}
+
+impl fmt::Display for Utm {
+    /// Formats as e.g. `18N 323394 4307395`, or `N 2000000 2000000` for UPS, to the meter.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.is_ups() {
+            write!(f, "{}", self.zone)?;
+        }
+        write!(
+            f,
+            "{} {:.0} {:.0}",
+            self.hemisphere.letter(),
+            self.easting,
+            self.northing
+        )
+    }
+}
+
+impl FromStr for Utm {
+    type Err = Error;
+
+    /// Parse e.g. `18N 323394 4307395`, `18 N 323394 4307395` or `N 2000000 2000000` (UPS).
+    ///
+    /// The letter is the hemisphere; MGRS latitude band letters other than `N` and `S` are also
+    /// accepted, since they imply the hemisphere.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let zone_len = s.chars().take_while(|c| c.is_ascii_digit()).count();
+        let zone = match zone_len {
+            0 => 0,
+            1 | 2 => s[..zone_len].parse().unwrap_or(0),
+            _ => return Err(Error::BadField("invalid UTM zone".into())),
+        };
+        if zone_len > 0 && !(1..=60).contains(&zone) {
+            return Err(Error::BadField("UTM zone must be within 1..=60".into()));
+        }
+        let rest = s[zone_len..].trim_start();
+        let letter = rest
+            .chars()
+            .next()
+            .ok_or(Error::BadField("missing UTM hemisphere".into()))?
+            .to_ascii_uppercase();
+        let hemisphere = match letter {
+            'N' => Hemisphere::North,
+            'S' => Hemisphere::South,
+            'C'..='M' if zone > 0 && letter != 'I' => Hemisphere::South,
+            'P'..='X' if zone > 0 && letter != 'O' => Hemisphere::North,
+            _ => return Err(Error::BadField("invalid UTM hemisphere".into())),
+        };
+        let numbers: Vec<&str> = rest[1..].split_whitespace().collect();
+        let [easting, northing] = numbers[..] else {
+            return Err(Error::BadField("UTM needs an easting and northing".into()));
+        };
+        let parse = |s: &str| {
+            s.parse::<f64>()
+                .map_err(|_| Error::BadField("invalid UTM easting or northing".into()))
+        };
+        Ok(Self {
+            zone,
+            hemisphere,
+            easting: parse(easting)?,
+            northing: parse(northing)?,
+        })
+    }
+}
+
+/// Grid convergence in degrees at a position: the bearing of UTM (or UPS) grid north from true
+/// north, positive east. Subtract it from a true bearing to get a grid bearing.
+pub fn grid_convergence(lat: f64, lon: f64) -> Result<f64, Error> {
+    check_lat_lon(lat, lon)?;
+    let lon = normalize_lon(lon);
+    if lat >= UTM_MAX_LAT {
+        return Ok(lon);
+    }
+    if lat < UTM_MIN_LAT {
+        return Ok(-lon);
+    }
+    let dlon = lon - central_meridian(utm_zone(lat, lon));
+    Ok(TransverseMercator::wgs84().convergence(lat, dlon))
+}
+
+/// Convert a position to an MGRS string, e.g. `18S UJ 23487 06483`, with `precision` digits
+/// per coordinate: 5 for 1 m, 4 for 10 m, ... 0 for the 100 km square only.
+///
+/// Coordinates are truncated, as MGRS references identify the square containing the position.
+/// The polar (UPS) regions beyond 80°S and 84°N are not supported.
+pub fn to_mgrs(lat: f64, lon: f64, precision: usize) -> Result<String, Error> {
+    if precision > 5 {
+        return Err(Error::BadField(
+            "MGRS precision must be 0 to 5 digits".into(),
+        ));
+    }
+    let utm = Utm::from_lat_lon(lat, lon)?;
+    if utm.is_ups() {
+        return Err(Error::BadField(
+            "MGRS polar regions are not supported".into(),
+        ));
+    }
+    let band = MGRS_LAT_BANDS[((lat / 8.0 + 10.0).floor() as usize).min(20)] as char;
+    let col = (utm.easting / 100_000.0).floor() as usize;
+    let col_letters = MGRS_COL_LETTERS[(utm.zone as usize - 1) % 3];
+    let e_letter = col_letters[col.clamp(1, 8) - 1] as char;
+    let row = (utm.northing / 100_000.0).floor() as usize;
+    let offset = if utm.zone % 2 == 0 { 5 } else { 0 };
+    let n_letter = MGRS_ROW_LETTERS[(row + offset) % 20] as char;
+
+    let mut mgrs = format!("{}{} {}{}", utm.zone, band, e_letter, n_letter);
+    if precision > 0 {
+        let scale = 10f64.powi(5 - precision as i32);
+        let e = (utm.easting.rem_euclid(100_000.0) / scale).floor();
+        let n = (utm.northing.rem_euclid(100_000.0) / scale).floor();
+        mgrs += &format!(" {:0p$} {:0p$}", e, n, p = precision);
+    }
+    Ok(mgrs)
+}
+
+/// Parse an MGRS string, returning the latitude and longitude of the center of the referenced
+/// square. Spaces are optional, e.g. `18SUJ2348706483` or `18S UJ 23487 06483`.
+pub fn from_mgrs(mgrs: &str) -> Result<(f64, f64), Error> {
+    let s: Vec<u8> = mgrs
+        .bytes()
+        .filter(|b| !b.is_ascii_whitespace())
+        .map(|b| b.to_ascii_uppercase())
+        .collect();
+    let zone_len = s.iter().take_while(|b| b.is_ascii_digit()).count();
+    if !(1..=2).contains(&zone_len) || s.len() < zone_len + 3 {
+        return Err(Error::BadField(
+            "MGRS must start with a zone and three letters".into(),
+        ));
+    }
+    let zone: u8 = std::str::from_utf8(&s[..zone_len])
+        .ok()
+        .and_then(|z| z.parse().ok())
+        .filter(|z| (1..=60).contains(z))
+        .ok_or(Error::BadField("MGRS zone must be within 1..=60".into()))?;
+    let (band, e_letter, n_letter) = (s[zone_len], s[zone_len + 1], s[zone_len + 2]);
+    let band_index = MGRS_LAT_BANDS
+        .iter()
+        .position(|b| *b == band)
+        .ok_or(Error::BadField("invalid MGRS latitude band".into()))?;
+    let col = MGRS_COL_LETTERS[(zone as usize - 1) % 3]
+        .iter()
+        .position(|b| *b == e_letter)
+        .ok_or(Error::BadField("invalid MGRS 100 km column letter".into()))?
+        + 1;
+    let offset = if zone % 2 == 0 { 5 } else { 0 };
+    let row = (MGRS_ROW_LETTERS
+        .iter()
+        .position(|b| *b == n_letter)
+        .ok_or(Error::BadField("invalid MGRS 100 km row letter".into()))?
+        + 20
+        - offset)
+        % 20;
+
+    let digits = &s[zone_len + 3..];
+    if digits.len() % 2 != 0 || digits.len() > 10 || !digits.iter().all(u8::is_ascii_digit) {
+        return Err(Error::BadField(
+            "MGRS needs an even number of up to 10 digits".into(),
+        ));
+    }
+    let precision = digits.len() / 2;
+    let scale = 10f64.powi(5 - precision as i32);
+    let parse = |d: &[u8]| -> f64 {
+        std::str::from_utf8(d)
+            .ok()
+            .and_then(|d| d.parse::<f64>().ok())
+            .unwrap_or(0.0)
+    };
+    let easting = col as f64 * 100_000.0 + (parse(&digits[..precision]) + 0.5) * scale;
+    let mut northing = row as f64 * 100_000.0 + (parse(&digits[precision..]) + 0.5) * scale;
+
+    // The row letters repeat every 2000 km: find the repetition within the latitude band.
+    let hemisphere = if band >= b'N' {
+        Hemisphere::North
+    } else {
+        Hemisphere::South
+    };
+    let band_lat = (band_index as f64 - 10.0) * 8.0;
+    let (_, mut band_northing) = TransverseMercator::wgs84().forward(band_lat, 0.0, UTM_K0);
+    if hemisphere == Hemisphere::South {
+        band_northing += UTM_FALSE_NORTHING_SOUTH;
+    }
+    let band_northing = (band_northing / 100_000.0).floor() * 100_000.0;
+    while northing < band_northing {
+        northing += 2_000_000.0;
+    }
+    Utm {
+        zone,
+        hemisphere,
+        easting,
+        northing,
+    }
+    .to_lat_lon()
+}
+
+/// Split an angle into whole degrees, minutes and seconds, rounding the seconds to `decimals`
+/// places without producing 60 seconds or minutes.
+fn split_angle(angle: f64, decimals: usize, with_seconds: bool) -> (u64, u64, f64) {
+    let scale = 10f64.powi(decimals as i32);
+    let units_per_degree = if with_seconds { 3600.0 } else { 60.0 } * scale;
+    let total = (angle.abs() * units_per_degree).round() as u64;
+    let units_per_minute = if with_seconds { 60.0 * scale } else { scale } as u64;
+    let deg = total / (units_per_degree as u64);
+    let rest = total % (units_per_degree as u64);
+    if with_seconds {
+        let min = rest / units_per_minute;
+        let sec = (rest % units_per_minute) as f64 / scale;
+        (deg, min, sec)
+    } else {
+        (deg, 0, rest as f64 / scale)
+    }
+}
+
+fn hemisphere_letter(value: f64, positive: char, negative: char) -> char {
+    if value < 0.0 {
+        negative
+    } else {
+        positive
+    }
+}
+
+/// Format a position as degrees, minutes and seconds, e.g. `38°51'23.94"N 77°03'49.11"W`.
+pub fn format_dms(lat: f64, lon: f64) -> String {
+    let dms = |value: f64, pos, neg| {
+        let (d, m, s) = split_angle(value, 2, true);
+        format!(
+            "{}°{:02}'{:05.2}\"{}",
+            d,
+            m,
+            s,
+            hemisphere_letter(value, pos, neg)
+        )
+    };
+    format!("{} {}", dms(lat, 'N', 'S'), dms(lon, 'E', 'W'))
+}
+
+/// Format a position as degrees and decimal minutes, e.g. `38°51.3990'N 77°03.8185'W`.
+pub fn format_ddm(lat: f64, lon: f64) -> String {
+    let ddm = |value: f64, pos, neg| {
+        let (d, _, m) = split_angle(value, 4, false);
+        format!("{}°{:07.4}'{}", d, m, hemisphere_letter(value, pos, neg))
+    };
+    format!("{} {}", ddm(lat, 'N', 'S'), ddm(lon, 'E', 'W'))
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Number(String),
+    Hemisphere(char),
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, Error> {
+    let mut tokens = vec![];
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '0'..='9' | '.' | '-' | '+' => {
+                let mut number = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    if !(c.is_ascii_digit() || c == '.') {
+                        break;
+                    }
+                    number.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Number(number));
+            }
+            'N' | 'S' | 'E' | 'W' | 'n' | 's' | 'e' | 'w' => {
+                tokens.push(Token::Hemisphere(c.to_ascii_uppercase()))
+            }
+            '°' | 'º' | '\'' | '′' | '’' | '"' | '″' | '”' | ':' | ',' | ';' => {}
+            c if c.is_whitespace() => {}
+            _ => {
+                return Err(Error::BadField(
+                    "unexpected character in coordinates".into(),
+                ))
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// Combine 1 to 3 numbers as degrees, minutes and seconds into a (signed) angle.
+fn parse_angle(numbers: &[&str]) -> Result<f64, Error> {
+    if numbers.is_empty() || numbers.len() > 3 {
+        return Err(Error::BadField("coordinate needs 1 to 3 numbers".into()));
+    }
+    let mut angle = 0.0;
+    let mut negative = false;
+    for (i, number) in numbers.iter().enumerate() {
+        if i > 0 && (number.starts_with('-') || number.starts_with('+')) {
+            return Err(Error::BadField("only degrees may have a sign".into()));
+        }
+        if i + 1 < numbers.len() && number.contains('.') {
+            return Err(Error::BadField(
+                "only the last coordinate part may have decimals".into(),
+            ));
+        }
+        let value: f64 = number
+            .parse()
+            .map_err(|_| Error::BadField("invalid number in coordinates".into()))?;
+        if i == 0 {
+            negative = number.starts_with('-');
+        } else if value >= 60.0 {
+            return Err(Error::BadField(
+                "minutes and seconds must be less than 60".into(),
+            ));
+        }
+        angle += value.abs() / 60f64.powi(i as i32);
+    }
+    Ok(if negative { -angle } else { angle })
+}
+
+/// Parse a latitude/longitude pair as typically entered by users, in decimal degrees, DMS or DDM,
+/// with hemisphere letters or signs. Returns `(lat, lon)` in degrees.
+///
+/// Accepted forms include `38.8566, -77.0637`, `38°51'23.9"N 77°03'49.1"W`, `N38 51.399
+/// W077 03.819`, and `38 51 23.9 -77 3 49.1`. Without hemisphere letters the latitude comes first.
+pub fn parse_lat_lon(s: &str) -> Result<(f64, f64), Error> {
+    let tokens = tokenize(s)?;
+    let letters: Vec<(usize, char)> = tokens
+        .iter()
+        .enumerate()
+        .filter_map(|(i, t)| match t {
+            Token::Hemisphere(c) => Some((i, *c)),
+            _ => None,
+        })
+        .collect();
+    fn number(t: &Token) -> Option<&str> {
+        match t {
+            Token::Number(n) => Some(n.as_str()),
+            _ => None,
+        }
+    }
+    fn unsigned_numbers(tokens: &[Token]) -> Result<Vec<&str>, Error> {
+        tokens
+            .iter()
+            .map(|t| number(t).filter(|n| !n.starts_with('-')))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(Error::BadField(
+                "negative coordinate with hemisphere letter".into(),
+            ))
+    }
+    let (first, second) = match letters[..] {
+        [] => {
+            let numbers: Vec<&str> = tokens.iter().filter_map(number).collect();
+            if numbers.len() % 2 != 0 {
+                return Err(Error::BadField(
+                    "latitude and longitude must have equal parts".into(),
+                ));
+            }
+            let (lat, lon) = numbers.split_at(numbers.len() / 2);
+            (parse_angle(lat)?, parse_angle(lon)?)
+        }
+        [(i, a), (j, b)] => {
+            // Letters either all precede or all follow their numbers.
+            let (lat_tokens, lon_tokens) = if i == 0 {
+                (&tokens[1..j], &tokens[j + 1..])
+            } else if j == tokens.len() - 1 {
+                (&tokens[..i], &tokens[i + 1..j])
+            } else {
+                return Err(Error::BadField("misplaced hemisphere letter".into()));
+            };
+            let apply = |angle: f64, letter: char| {
+                if letter == 'S' || letter == 'W' {
+                    -angle
+                } else {
+                    angle
+                }
+            };
+            let first = apply(parse_angle(&unsigned_numbers(lat_tokens)?)?, a);
+            let second = apply(parse_angle(&unsigned_numbers(lon_tokens)?)?, b);
+            match (a, b) {
+                ('N' | 'S', 'E' | 'W') => (first, second),
+                ('E' | 'W', 'N' | 'S') => (second, first),
+                _ => {
+                    return Err(Error::BadField(
+                        "need one latitude and one longitude".into(),
+                    ))
+                }
+            }
+        }
+        _ => {
+            return Err(Error::BadField(
+                "need zero or two hemisphere letters".into(),
+            ))
+        }
+    };
+    let (lat, lon) = (first, second);
+    if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&lon) {
+        return Err(Error::BadField("coordinates out of range".into()));
+    }
+    Ok((lat, lon))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::base::Point;
+
+    use super::*;
+
+    fn assert_close(a: (f64, f64), b: (f64, f64), tolerance: f64) {
+        assert!(
+            (a.0 - b.0).abs() < tolerance && (a.1 - b.1).abs() < tolerance,
+            "{:?} != {:?}",
+            a,
+            b
+        );
+    }
+
+    #[test]
+    fn test_utm() {
+        let utm = Utm::from_lat_lon(48.8582, 2.2945).unwrap();
+        assert_eq!(utm.to_string(), "31N 448252 5411933");
+        assert_close(utm.to_lat_lon().unwrap(), (48.8582, 2.2945), 1e-9);
+
+        let utm = Utm::from_lat_lon(-33.857, 151.215).unwrap();
+        assert_eq!(utm.to_string(), "56S 334873 6252266");
+        assert_close(utm.to_lat_lon().unwrap(), (-33.857, 151.215), 1e-9);
+
+        // Norway and Svalbard
+        assert_eq!(Utm::from_lat_lon(60.0, 4.0).unwrap().zone, 32);
+        assert_eq!(Utm::from_lat_lon(75.0, 8.0).unwrap().zone, 31);
+        assert_eq!(Utm::from_lat_lon(75.0, 10.0).unwrap().zone, 33);
+
+        let parsed: Utm = "31 n 448252 5411933".parse().unwrap();
+        assert_eq!(parsed.zone, 31);
+        assert_eq!(parsed.easting, 448252.0);
+        assert!("61N 448252 5411933".parse::<Utm>().is_err());
+        assert!("31N 448252".parse::<Utm>().is_err());
+    }
+
+    #[test]
+    fn test_ups() {
+        let pole = Utm::from_lat_lon(90.0, 0.0).unwrap();
+        assert_eq!(pole.to_string(), "N 2000000 2000000");
+        for (lat, lon) in [(87.0, 45.0), (84.5, -120.0), (-85.0, 10.0), (-89.0, -170.0)] {
+            let ups = Utm::from_lat_lon(lat, lon).unwrap();
+            assert!(ups.is_ups());
+            assert_close(ups.to_lat_lon().unwrap(), (lat, lon), 1e-9);
+            let parsed: Utm = ups.to_string().parse().unwrap();
+            let (lat1, lon1) = parsed.to_lat_lon().unwrap();
+            let error = Point::from_lat_lon(lat, lon).distance_to(&Point::from_lat_lon(lat1, lon1));
+            assert!(error < 1.0);
+        }
+        // 1 degree from the pole is 111.7 km along the meridian, scaled by k0 = 0.994.
+        let ups = Utm::from_lat_lon(89.0, 90.0).unwrap();
+        assert!((ups.easting - UPS_FALSE_ORIGIN - 111_027.0).abs() < 5.0);
+        assert!((ups.northing - UPS_FALSE_ORIGIN).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_grid_convergence() {
+        // Grid north from the change in UTM coordinates along a short northward step.
+        let tm = TransverseMercator::wgs84();
+        for (lat, dlon) in [
+            (48.8582, -0.7055),
+            (-33.857, 2.215),
+            (70.0, 3.0),
+            (0.0, 1.0),
+        ] {
+            let (x0, y0) = tm.forward(lat, dlon, UTM_K0);
+            let (x1, y1) = tm.forward(lat + 1e-5, dlon, UTM_K0);
+            let expected = -(x1 - x0).atan2(y1 - y0).to_degrees();
+            let gamma = tm.convergence(lat, dlon);
+            assert!((gamma - expected).abs() < 1e-5, "{} != {}", gamma, expected);
+        }
+        let gamma = grid_convergence(48.8582, 2.2945).unwrap();
+        assert!((gamma + 0.5313).abs() < 1e-3, "{}", gamma);
+        assert_eq!(grid_convergence(0.0, 3.0).unwrap(), 0.0);
+        assert_eq!(grid_convergence(85.0, 30.0).unwrap(), 30.0);
+        assert_eq!(grid_convergence(-85.0, 30.0).unwrap(), -30.0);
+    }
+
+    #[test]
+    fn test_mgrs() {
+        assert_eq!(to_mgrs(48.8582, 2.2945, 5).unwrap(), "31U DQ 48251 11932");
+        assert_eq!(to_mgrs(48.8582, 2.2945, 3).unwrap(), "31U DQ 482 119");
+        assert_eq!(to_mgrs(48.8582, 2.2945, 0).unwrap(), "31U DQ");
+        assert_eq!(to_mgrs(-33.857, 151.215, 5).unwrap(), "56H LH 34873 52266");
+        assert!(to_mgrs(0.0, 0.0, 6).is_err());
+        assert!(to_mgrs(85.0, 0.0, 5).is_err());
+
+        let (lat, lon) = from_mgrs("31udq4825111932").unwrap();
+        assert!(
+            crate::base::Point::from_lat_lon(lat, lon)
+                .distance_to(&crate::base::Point::from_lat_lon(48.8582, 2.2945))
+                < 1.5
+        );
+        // Round trips across zones, bands and hemispheres
+        for (lat, lon) in [
+            (38.8895, -77.0352),
+            (-54.8, -68.3),
+            (0.1, 0.1),
+            (-0.1, 179.9),
+            (64.1, -21.9),
+            (83.9, 10.0),
+            (-79.9, 120.5),
+        ] {
+            let mgrs = to_mgrs(lat, lon, 5).unwrap();
+            let (lat1, lon1) = from_mgrs(&mgrs).unwrap();
+            assert_eq!(to_mgrs(lat1, lon1, 5).unwrap(), mgrs, "{}", mgrs);
+            assert_close((lat1, lon1), (lat, lon), 1e-4);
+        }
+        assert!(from_mgrs("31U DQ 4825 119").is_err());
+        assert!(from_mgrs("31I DQ").is_err());
+        assert!(from_mgrs("31U IQ").is_err());
+    }
+
+    #[test]
+    fn test_dms() {
+        assert_eq!(
+            format_dms(38.856650, -77.063642),
+            "38°51'23.94\"N 77°03'49.11\"W"
+        );
+        assert_eq!(
+            format_ddm(38.856650, -77.063642),
+            "38°51.3990'N 77°03.8185'W"
+        );
+        // Rounding doesn't produce 60 seconds
+        assert_eq!(format_dms(-0.9999999, 0.0), "1°00'00.00\"S 0°00'00.00\"E");
+
+        for s in [
+            "38.85665, -77.063642",
+            "38.85665 -77.063642",
+            "38°51'23.94\"N 77°03'49.11\"W",
+            "38°51′23.94″N, 77°3′49.11″W",
+            "N38 51 23.94 W77 03 49.11",
+            "38 51.399N 077 03.81852W",
+            "W 77 03.81852 N 38 51.399",
+            "38:51:23.94 -77:03:49.11",
+        ] {
+            assert_close(parse_lat_lon(s).unwrap(), (38.85665, -77.063642), 1e-5);
+        }
+        for s in [
+            "38.8 ",
+            "38 61 N 77 W",
+            "91 N 0 E",
+            "38.5 30 N 77 W",
+            "38 N 77 N",
+            "-38 N 77 W",
+            "38x 77",
+        ] {
+            assert!(parse_lat_lon(s).is_err(), "{}", s);
+        }
+    }
+}
@@ -1,12 +1,15 @@
+use std::fmt;
+
 use quick_xml::events::Event;
 
 use crate::{
-    base::{Cot, CotBase},
+    base::{from_str, from_str_with, locate_error, Cot, CotBase},
+    time::TimeParsing,
     Error,
 };
 
 /// A CoT message struct with unparsed \<detail\> section, which is captured as a raw
-/// `Vec<String>`.
+/// `Vec<String>`, with one entry for each top-level element in the section.
 pub type CotUnparsedDetail = Cot<Vec<String>>;
 
 impl From<CotBase> for CotUnparsedDetail {
@@ -38,11 +41,13 @@ impl From<CotBase> for CotUnparsedDetail {
 /// let cot: Cot<Foo> = quick_xml::de::from_str(input_str).unwrap();
 /// ```
 /// where `Foo` is your known type struct for the detail section, which implements `Deserialize`.
+/// [`crate::base::from_str()`] does the same, with errors giving the path and position of the
+/// field which failed.
 pub fn parse(input: &str) -> Result<CotUnparsedDetail, Error> {
     let mut reader = quick_xml::Reader::from_str(input);
     reader.config_mut().trim_text(true);
-    let detail = extract_detail(reader)?;
-    let cot_base: CotBase = quick_xml::de::from_str(input)?;
+    let detail = extract_detail(reader).map_err(|e| locate_error(input, e))?;
+    let cot_base: CotBase = from_str(input)?;
     let mut cot: CotUnparsedDetail = cot_base.into();
     cot.detail = detail;
     Ok(cot)
@@ -55,22 +60,27 @@ pub fn extract_detail(mut reader: quick_xml::reader::Reader<&[u8]>) -> Result<Ve
     let mut is_detail = false;
     loop {
         match reader.read_event() {
-            Ok(Event::Start(ref e)) => {
-                if e.name().as_ref() == b"detail" {
-                    is_detail = true;
-                }
+            Ok(Event::Start(ref e)) if e.name().as_ref() == b"detail" => {
+                is_detail = true;
             }
-            Ok(Event::Empty(ref e)) => {
-                if is_detail {
-                    // XXX there should be a better way to get raw lines here?
-                    detail.push(format!("<{}/>", String::from_utf8_lossy(e)));
-                }
+            Ok(Event::Start(ref e)) if is_detail => {
+                // Capture nested elements, e.g. `<shape>`, including all of their content.
+                let end = e.to_end().into_owned();
+                let inner = reader.read_text(end.name())?;
+                detail.push(format!(
+                    "<{}>{}</{}>",
+                    String::from_utf8_lossy(e),
+                    inner,
+                    String::from_utf8_lossy(end.name().as_ref())
+                ));
+            }
+            Ok(Event::Empty(ref e)) if is_detail => {
+                // XXX there should be a better way to get raw lines here?
+                detail.push(format!("<{}/>", String::from_utf8_lossy(e)));
             }
             Ok(Event::Text(_e)) => {}
-            Ok(Event::End(ref e)) => {
-                if e.name().as_ref() == b"detail" {
-                    is_detail = false;
-                }
+            Ok(Event::End(ref e)) if e.name().as_ref() == b"detail" => {
+                is_detail = false;
             }
             Err(e) => return Err(Error::Xml(e)),
             Ok(Event::Eof) => break,
@@ -80,6 +90,235 @@ pub fn extract_detail(mut reader: quick_xml::reader::Reader<&[u8]>) -> Result<Ve
     Ok(detail)
 }
 
+/// A part of a CoT message skipped by [`parse_lenient()`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    /// Byte offset of the skipped text in the input.
+    pub position: usize,
+    /// The skipped text, e.g. a malformed `<detail>` element.
+    pub text: String,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "skipped at byte {}: {}: {}",
+            self.position, self.message, self.text
+        )
+    }
+}
+
+/// Like [`parse()`], but skip malformed top-level elements in the `<detail>` section instead of
+/// failing, e.g. elements with unquoted attributes or missing end tags. Returns the event with the
+/// well-formed detail elements, and a [`Diagnostic`] for each skipped part.
+///
+/// The rest of the event must still be valid, but timestamps are parsed with
+/// [`TimeParsing::Lenient`].
+pub fn parse_lenient(input: &str) -> Result<(CotUnparsedDetail, Vec<Diagnostic>), Error> {
+    let mut diagnostics = vec![];
+    let Some(content) = find_detail(input).map_err(|e| locate_error(input, e))? else {
+        return Ok((parse(input)?, diagnostics));
+    };
+    // Blank out the detail content, keeping line breaks and byte offsets for error positions.
+    let blank: String = input[content.clone()]
+        .chars()
+        .map(|c| match c {
+            '\n' => "\n".to_string(),
+            c => " ".repeat(c.len_utf8()),
+        })
+        .collect();
+    let base_input = format!(
+        "{}{}{}",
+        &input[..content.start],
+        blank,
+        &input[content.end..]
+    );
+    let cot_base: CotBase = from_str_with(&base_input, TimeParsing::Lenient)?;
+    let mut cot: CotUnparsedDetail = cot_base.into();
+    cot.detail = split_detail(input, content, &mut diagnostics)
+        .into_iter()
+        .filter_map(|(start, element)| match check_element(element) {
+            Ok(()) => Some(element.to_string()),
+            Err(e) => {
+                diagnostics.push(Diagnostic {
+                    position: start,
+                    text: element.to_string(),
+                    message: format!("malformed element: {}", e),
+                });
+                None
+            }
+        })
+        .collect();
+    diagnostics.sort_by_key(|d| d.position);
+    Ok((cot, diagnostics))
+}
+
+type Range = std::ops::Range<usize>;
+
+/// Byte range of the content of the `<detail>` section, if it isn't empty.
+fn find_detail(input: &str) -> Result<Option<Range>, Error> {
+    let mut reader = quick_xml::Reader::from_str(input);
+    loop {
+        match reader.read_event()? {
+            Event::Start(ref e) if e.name().as_ref() == b"detail" => {
+                let start = reader.buffer_position() as usize;
+                let Some(len) = input[start..].rfind("</detail") else {
+                    return Err(Error::BadField("detail section has no end tag".into()));
+                };
+                return Ok(Some(start..start + len));
+            }
+            Event::Empty(ref e) if e.name().as_ref() == b"detail" => return Ok(None),
+            Event::Eof => return Ok(None),
+            _ => {}
+        }
+    }
+}
+
+/// Index after the `>` ending the tag starting at `start`, skipping quoted attribute values.
+fn tag_end(input: &str, start: usize) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in input[start..].char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if q == c => quote = None,
+            (None, '>') => return Some(start + i + 1),
+            _ => {}
+        }
+    }
+    // Unbalanced quotes: end at the first '>'.
+    input[start..].find('>').map(|i| start + i + 1)
+}
+
+/// Split the `<detail>` content at `range` of `input` into top-level elements, with their byte
+/// offsets. Text, comments and stray end tags are skipped, with diagnostics for the latter.
+fn split_detail<'a>(
+    input: &'a str,
+    range: Range,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Vec<(usize, &'a str)> {
+    let content = &input[..range.end];
+    let mut elements = vec![];
+    let mut pos = range.start;
+    while let Some(i) = content[pos..].find('<') {
+        let start = pos + i;
+        let rest = &content[start..];
+        let skip_to = |terminator: &str| {
+            rest.find(terminator)
+                .map_or(content.len(), |j| start + j + terminator.len())
+        };
+        if rest.starts_with("<!--") {
+            pos = skip_to("-->");
+            continue;
+        }
+        if rest.starts_with("<![CDATA[") {
+            pos = skip_to("]]>");
+            continue;
+        }
+        if rest.starts_with("<?") {
+            pos = skip_to("?>");
+            continue;
+        }
+        let Some(end) = tag_end(content, start) else {
+            diagnostics.push(Diagnostic {
+                position: start,
+                text: rest.to_string(),
+                message: "unterminated tag".to_string(),
+            });
+            break;
+        };
+        if rest.starts_with("</") {
+            diagnostics.push(Diagnostic {
+                position: start,
+                text: content[start..end].to_string(),
+                message: "end tag without start tag".to_string(),
+            });
+            pos = end;
+            continue;
+        }
+        if content[..end].ends_with("/>") {
+            elements.push((start, &content[start..end]));
+            pos = end;
+            continue;
+        }
+        match element_end(content, start, end) {
+            Some(element_end) => {
+                elements.push((start, &content[start..element_end]));
+                pos = element_end;
+            }
+            None => {
+                // Skip only the start tag, to keep any following elements.
+                diagnostics.push(Diagnostic {
+                    position: start,
+                    text: content[start..end].to_string(),
+                    message: "element has no end tag".to_string(),
+                });
+                pos = end;
+            }
+        }
+    }
+    elements
+}
+
+/// Name of the tag starting at the beginning of `tag`, without `<` or `</`.
+fn tag_name(tag: &str) -> &str {
+    let name = tag.trim_start_matches('<').trim_start_matches('/');
+    let end = name
+        .find(|c: char| c.is_whitespace() || c == '/' || c == '>')
+        .unwrap_or(name.len());
+    &name[..end]
+}
+
+/// Index after the end tag closing the element whose start tag is `content[start..pos]`.
+/// End tags not matching the innermost open element are ignored.
+fn element_end(content: &str, start: usize, mut pos: usize) -> Option<usize> {
+    let mut open = vec![tag_name(&content[start..])];
+    while let Some(i) = content[pos..].find('<') {
+        let start = pos + i;
+        let rest = &content[start..];
+        let terminator = if rest.starts_with("<!--") {
+            Some("-->")
+        } else if rest.starts_with("<![CDATA[") {
+            Some("]]>")
+        } else {
+            None
+        };
+        if let Some(terminator) = terminator {
+            pos = start + rest.find(terminator)? + terminator.len();
+            continue;
+        }
+        pos = tag_end(content, start)?;
+        if rest.starts_with("</") {
+            if open.last() == Some(&tag_name(rest)) {
+                open.pop();
+                if open.is_empty() {
+                    return Some(pos);
+                }
+            }
+        } else if !content[..pos].ends_with("/>") && !rest.starts_with("<?") {
+            open.push(tag_name(rest));
+        }
+    }
+    None
+}
+
+/// Check that `element` is well-formed XML with valid attributes.
+fn check_element(element: &str) -> Result<(), Error> {
+    let mut reader = quick_xml::Reader::from_str(element);
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) | Event::Empty(e) => {
+                for attr in e.attributes() {
+                    attr?;
+                }
+            }
+            Event::Eof => return Ok(()),
+            _ => {}
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::collections::HashSet;
@@ -98,6 +337,54 @@ mod test {
         test_expected_detail(COT_STRIKE_EXAMPLE, &COT_STRIKE_DETAIL_LINES)
     }
 
+    #[test]
+    fn test_detail_parse_lenient() {
+        let (cot, diagnostics) = super::parse_lenient(COT_TRACK_EXAMPLE).unwrap();
+        assert_eq!(cot, super::parse(COT_TRACK_EXAMPLE).unwrap());
+        assert!(diagnostics.is_empty());
+
+        // The malformed `<_tasking= ...>` element is skipped.
+        let (cot, diagnostics) = super::parse_lenient(COT_STRIKE_EXAMPLE).unwrap();
+        let expected: Vec<_> = COT_STRIKE_DETAIL_LINES
+            .iter()
+            .copied()
+            .filter(|l| !l.starts_with("<_tasking"))
+            .collect();
+        assert_eq!(cot.detail, expected);
+        assert_eq!(cot.uid, "FAB.BOOT.a-h-G-U-C-I_MSN-01");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].text, COT_STRIKE_DETAIL_LINES[3]);
+        assert_eq!(
+            &COT_STRIKE_EXAMPLE[diagnostics[0].position..][..9],
+            "<_tasking"
+        );
+    }
+
+    #[test]
+    fn test_detail_parse_lenient_recovers() {
+        let input = COT_TRACK_EXAMPLE.replace(
+            "<contact callsign=\"BLAMO-IDM1-3V\"/>",
+            "<shape><ellipse major='1'/></polyline></shape> <open a='1'> </stray> <!-- <x> --> \
+             <contact callsign=\"BLAMO-IDM1-3V\"/>",
+        );
+        assert!(super::parse(&input).is_err());
+        let (cot, diagnostics) = super::parse_lenient(&input).unwrap();
+        assert_eq!(cot.detail, COT_TRACK_DETAIL_LINES);
+        let skipped: Vec<_> = diagnostics.iter().map(|d| d.text.as_str()).collect();
+        assert_eq!(
+            skipped,
+            [
+                "<shape><ellipse major='1'/></polyline></shape>",
+                "<open a='1'>",
+                "</stray>"
+            ]
+        );
+
+        // The base event must be valid.
+        let input = COT_TRACK_EXAMPLE.replace("lat=", "lat=x");
+        assert!(super::parse_lenient(&input).is_err());
+    }
+
     fn test_expected_detail(input: &str, expected_lines: &[&str]) {
         let cot = super::parse(input).unwrap();
         let mut expected_lines: HashSet<&str> = HashSet::from_iter(expected_lines.iter().cloned());
@@ -0,0 +1,363 @@
+//! Terrain elevation lookup from local Digital Terrain Elevation Data (DTED) level 0, 1 and 2
+//! tiles, as defined by MIL-PRF-89020B.
+//!
+//! DTED elevations are in meters above mean sea level (EGM96). [`Dted::fill_hae()`] converts them
+//! to the height above ellipsoid used in CoT [`Point`]s with the bundled EGM96 geoid, see
+//! [`crate::geoid`].
+//!
+//! ```rust,no_run
+//! # use cot_proto::base::Point;
+//! # use cot_proto::dted::Dted;
+//! # fn run() -> Result<(), cot_proto::Error> {
+//! let dted = Dted::open_dir("/data/dted")?;
+//! let point = Point::from_lat_lon(36.1, -115.2);
+//! if let Some((msl, level)) = dted.elevation(point.lat, point.lon) {
+//!     println!("{} m MSL from {}", msl, level);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+use crate::base::{Point, UNKNOWN_VALUE};
+#[cfg(feature = "tak")]
+use crate::tak::detail::PrecisionLocation;
+use crate::Error;
+
+const UHL_LEN: usize = 80;
+const DSI_LEN: usize = 648;
+const ACC_LEN: usize = 2700;
+/// Start of the first data record: after the UHL, DSI and ACC headers.
+const DATA_OFFSET: usize = UHL_LEN + DSI_LEN + ACC_LEN;
+const RECORD_SENTINEL: u8 = 0xaa;
+/// Elevation value of posts with no data.
+const VOID_VALUE: i16 = -32767;
+
+/// DTED level, i.e. resolution: about 900 m, 90 m and 30 m post spacing at the equator.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DtedLevel {
+    Level0 = 0,
+    Level1 = 1,
+    Level2 = 2,
+}
+
+impl DtedLevel {
+    /// Altitude source name used in [`PrecisionLocation::altsrc`], e.g. `DTED0`.
+    pub fn altsrc(self) -> &'static str {
+        match self {
+            DtedLevel::Level0 => "DTED0",
+            DtedLevel::Level1 => "DTED1",
+            DtedLevel::Level2 => "DTED2",
+        }
+    }
+}
+
+impl fmt::Display for DtedLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.altsrc())
+    }
+}
+
+/// A DTED tile: a grid of elevation posts, normally covering one degree cell.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DtedTile {
+    pub level: DtedLevel,
+    /// Latitude of the south west corner in degrees.
+    pub lat_origin: f64,
+    /// Longitude of the south west corner in degrees.
+    pub lon_origin: f64,
+    /// Post spacing in degrees.
+    lat_interval: f64,
+    lon_interval: f64,
+    lat_count: usize,
+    lon_count: usize,
+    /// Elevations in meters MSL, by longitude line (west to east), then south to north.
+    posts: Vec<i16>,
+}
+
+/// Parse an ASCII header field.
+fn field<T: std::str::FromStr>(header: &[u8], range: std::ops::Range<usize>) -> Result<T, Error> {
+    std::str::from_utf8(&header[range])
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+        .ok_or(Error::BadField("invalid DTED header field".into()))
+}
+
+/// Parse a `DDDMMSSH` header angle.
+fn angle(header: &[u8], start: usize) -> Result<f64, Error> {
+    let degrees: f64 = field(header, start..start + 3)?;
+    let minutes: f64 = field(header, start + 3..start + 5)?;
+    let seconds: f64 = field(header, start + 5..start + 7)?;
+    let value = degrees + minutes / 60.0 + seconds / 3600.0;
+    match header[start + 7] {
+        b'N' | b'E' => Ok(value),
+        b'S' | b'W' => Ok(-value),
+        _ => Err(Error::BadField("invalid DTED hemisphere".into())),
+    }
+}
+
+impl DtedTile {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::read(BufReader::new(File::open(path)?))
+    }
+
+    /// Read a DTED file: the UHL, DSI and ACC headers, then a data record per longitude line.
+    pub fn read<R: Read>(mut reader: R) -> Result<Self, Error> {
+        let mut header = vec![0; DATA_OFFSET];
+        reader.read_exact(&mut header)?;
+        if !header.starts_with(b"UHL")
+            || &header[UHL_LEN..UHL_LEN + 3] != b"DSI"
+            || &header[UHL_LEN + DSI_LEN..UHL_LEN + DSI_LEN + 3] != b"ACC"
+        {
+            return Err(Error::BadField("not a DTED file".into()));
+        }
+        let lon_origin = angle(&header, 4)?;
+        let lat_origin = angle(&header, 12)?;
+        // Intervals are in tenths of arc seconds.
+        let lon_interval = field::<f64>(&header, 20..24)? / 36_000.0;
+        let lat_interval = field::<f64>(&header, 24..28)? / 36_000.0;
+        let lon_count: usize = field(&header, 47..51)?;
+        let lat_count: usize = field(&header, 51..55)?;
+        if lon_interval <= 0.0 || lat_interval <= 0.0 || lon_count < 2 || lat_count < 2 {
+            return Err(Error::BadField("invalid DTED grid size".into()));
+        }
+        let level = match &header[UHL_LEN + 59..UHL_LEN + 64] {
+            b"DTED0" => DtedLevel::Level0,
+            b"DTED1" => DtedLevel::Level1,
+            b"DTED2" => DtedLevel::Level2,
+            _ => return Err(Error::BadField("unknown DTED level".into())),
+        };
+
+        let mut posts = Vec::with_capacity(lon_count * lat_count);
+        let mut record = vec![0; 12 + 2 * lat_count];
+        for _ in 0..lon_count {
+            reader.read_exact(&mut record)?;
+            if record[0] != RECORD_SENTINEL {
+                return Err(Error::BadField("invalid DTED data record".into()));
+            }
+            posts.extend(record[8..8 + 2 * lat_count].chunks_exact(2).map(|b| {
+                // Signed magnitude
+                let value = u16::from_be_bytes([b[0], b[1]]);
+                let magnitude = (value & 0x7fff) as i16;
+                match value & 0x8000 {
+                    0 => magnitude,
+                    _ => -magnitude,
+                }
+            }));
+        }
+        Ok(Self {
+            level,
+            lat_origin,
+            lon_origin,
+            lat_interval,
+            lon_interval,
+            lat_count,
+            lon_count,
+            posts,
+        })
+    }
+
+    /// Whether the tile covers the given position.
+    pub fn contains(&self, lat: f64, lon: f64) -> bool {
+        let (x, y) = self.grid_position(lat, lon);
+        (0.0..=(self.lon_count - 1) as f64).contains(&x)
+            && (0.0..=(self.lat_count - 1) as f64).contains(&y)
+    }
+
+    fn grid_position(&self, lat: f64, lon: f64) -> (f64, f64) {
+        let x = (lon - self.lon_origin) / self.lon_interval;
+        let y = (lat - self.lat_origin) / self.lat_interval;
+        (x, y)
+    }
+
+    fn post(&self, lon_index: usize, lat_index: usize) -> Option<f64> {
+        match self.posts[lon_index * self.lat_count + lat_index] {
+            VOID_VALUE => None,
+            elevation => Some(elevation as f64),
+        }
+    }
+
+    /// Elevation in meters above mean sea level, interpolated bilinearly between posts. `None`
+    /// outside the tile or next to void posts.
+    pub fn elevation(&self, lat: f64, lon: f64) -> Option<f64> {
+        if !self.contains(lat, lon) {
+            return None;
+        }
+        let (x, y) = self.grid_position(lat, lon);
+        let col = (x.floor() as usize).min(self.lon_count - 2);
+        let row = (y.floor() as usize).min(self.lat_count - 2);
+        let (fx, fy) = (x - col as f64, y - row as f64);
+        let south = self.post(col, row)? * (1.0 - fx) + self.post(col + 1, row)? * fx;
+        let north = self.post(col, row + 1)? * (1.0 - fx) + self.post(col + 1, row + 1)? * fx;
+        Some(south * (1.0 - fy) + north * fy)
+    }
+}
+
+/// A set of DTED tiles, possibly of different levels.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Dted {
+    tiles: Vec<DtedTile>,
+}
+
+impl Dted {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load all `.dt0`, `.dt1` and `.dt2` files under `dir`, e.g. in the usual
+    /// `dted/w116/n36.dt1` layout.
+    pub fn open_dir<P: AsRef<Path>>(dir: P) -> Result<Self, Error> {
+        let mut dted = Self::new();
+        dted.add_dir(dir.as_ref())?;
+        Ok(dted)
+    }
+
+    fn add_dir(&mut self, dir: &Path) -> Result<(), Error> {
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                self.add_dir(&path)?;
+                continue;
+            }
+            let is_dted = path.extension().and_then(|e| e.to_str()).is_some_and(|e| {
+                ["dt0", "dt1", "dt2"]
+                    .iter()
+                    .any(|ext| e.eq_ignore_ascii_case(ext))
+            });
+            if is_dted {
+                self.add(DtedTile::open(&path)?);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn add(&mut self, tile: DtedTile) {
+        self.tiles.push(tile);
+    }
+
+    pub fn tiles(&self) -> &[DtedTile] {
+        &self.tiles
+    }
+
+    /// Elevation in meters above mean sea level from the highest level tile with data for the
+    /// position, and that level.
+    pub fn elevation(&self, lat: f64, lon: f64) -> Option<(f64, DtedLevel)> {
+        self.tiles
+            .iter()
+            .filter_map(|tile| Some((tile.elevation(lat, lon)?, tile.level)))
+            .max_by_key(|(_, level)| *level)
+    }
+
+    /// Set an unknown `hae` of `point` from the terrain elevation, converted to height above
+    /// ellipsoid with the geoid returned by [`geoid::default_geoid()`](crate::geoid::default_geoid).
+    /// Returns the level of the DTED used, or `None` if `hae` is already known or there is no data
+    /// for the position.
+    pub fn fill_hae(&self, point: &mut Point) -> Option<DtedLevel> {
+        if point.hae != UNKNOWN_VALUE {
+            return None;
+        }
+        let (msl, level) = self.elevation(point.lat, point.lon)?;
+        *point = point.with_msl(msl as f32);
+        Some(level)
+    }
+
+    /// Like [`Dted::fill_hae()`], also setting `precisionlocation`'s `altsrc` to the DTED level,
+    /// e.g. `DTED1`, as ATAK does for terrain altitudes.
+    #[cfg(feature = "tak")]
+    pub fn fill_hae_precision(
+        &self,
+        point: &mut Point,
+        precisionlocation: &mut PrecisionLocation,
+    ) -> Option<DtedLevel> {
+        let level = self.fill_hae(point)?;
+        precisionlocation.altsrc = level.altsrc().to_string();
+        Some(level)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// DTED level 1 style tile at 48N 2E with 5 x 5 posts (15' spacing), elevation
+    /// `100 * lon_index + lat_index`, with a void post in the north east corner.
+    fn test_tile() -> Vec<u8> {
+        let mut dted = vec![b' '; DATA_OFFSET];
+        // Origin, intervals in tenths of seconds, vertical accuracy, security, reference and
+        // number of longitude lines and latitude points.
+        let uhl =
+            "UHL1 0020000E 0480000N 9000 9000 0000 U__ ____________ 0005 0005".replace(' ', "");
+        dted[..uhl.len()].copy_from_slice(uhl.as_bytes());
+        dted[UHL_LEN..UHL_LEN + 3].copy_from_slice(b"DSI");
+        dted[UHL_LEN + 59..UHL_LEN + 64].copy_from_slice(b"DTED1");
+        dted[UHL_LEN + DSI_LEN..UHL_LEN + DSI_LEN + 3].copy_from_slice(b"ACC");
+        for lon in 0..5u16 {
+            dted.extend_from_slice(&[RECORD_SENTINEL, 0, 0, lon as u8, 0, lon as u8, 0, 0]);
+            for lat in 0..5u16 {
+                let value = match (lon, lat) {
+                    (4, 4) => 0xffff,
+                    _ => 100 * lon + lat,
+                };
+                dted.extend_from_slice(&value.to_be_bytes());
+            }
+            dted.extend_from_slice(&[0; 4]);
+        }
+        dted
+    }
+
+    #[test]
+    fn test_dted_tile() {
+        let tile = DtedTile::read(&test_tile()[..]).unwrap();
+        assert_eq!(tile.level, DtedLevel::Level1);
+        assert_eq!((tile.lat_origin, tile.lon_origin), (48.0, 2.0));
+        assert_eq!(tile.elevation(48.0, 2.0), Some(0.0));
+        assert_eq!(tile.elevation(48.25, 2.5), Some(201.0));
+        assert_eq!(tile.elevation(48.125, 2.125), Some(50.5));
+        assert_eq!(tile.elevation(49.0, 2.0), Some(4.0));
+        // Next to the void post
+        assert_eq!(tile.elevation(48.9, 2.9), None);
+        assert_eq!(tile.elevation(47.9, 2.5), None);
+        assert_eq!(tile.elevation(48.5, 3.1), None);
+
+        let mut bad = test_tile();
+        bad[DATA_OFFSET] = 0;
+        assert!(DtedTile::read(&bad[..]).is_err());
+        assert!(DtedTile::read(&test_tile()[..DATA_OFFSET + 10]).is_err());
+    }
+
+    #[test]
+    fn test_dted_dir() {
+        let dir = std::env::temp_dir().join(format!("cot-proto-dted-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("e002")).unwrap();
+        std::fs::write(dir.join("e002/n48.dt1"), test_tile()).unwrap();
+        std::fs::write(dir.join("e002/readme.txt"), "not DTED").unwrap();
+        let dted = Dted::open_dir(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+        let dted = dted.unwrap();
+        assert_eq!(dted.tiles().len(), 1);
+        assert_eq!(dted.elevation(48.25, 2.5), Some((201.0, DtedLevel::Level1)));
+        assert_eq!(dted.elevation(10.0, 10.0), None);
+
+        let mut point = Point::from_lat_lon(48.25, 2.5);
+        assert_eq!(dted.fill_hae(&mut point), Some(DtedLevel::Level1));
+        let hae = point.hae;
+        assert_eq!(hae, Point::from_lat_lon(48.25, 2.5).with_msl(201.0).hae);
+        assert!((hae - 201.0 - 45.3).abs() < 0.1, "{}", hae);
+        // Known altitudes are kept
+        assert_eq!(dted.fill_hae(&mut point), None);
+        assert_eq!(point.hae, hae);
+
+        #[cfg(feature = "tak")]
+        {
+            let mut point = Point::from_lat_lon(48.25, 2.5);
+            let mut precisionlocation = PrecisionLocation::default();
+            let level = dted.fill_hae_precision(&mut point, &mut precisionlocation);
+            assert_eq!(level, Some(DtedLevel::Level1));
+            assert_eq!(precisionlocation.altsrc, "DTED1");
+        }
+    }
+}
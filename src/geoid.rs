@@ -0,0 +1,265 @@
+//! Geoid heights, for converting between height above ellipsoid (HAE), as used in CoT
+//! [`Point`](crate::base::Point)s, and height above mean sea level (MSL), as reported by most
+//! sensors and displayed by ATAK.
+//!
+//! `MSL = HAE - N`, where `N` is the geoid height (undulation) at the position, between about
+//! -107 m and +86 m for EGM96.
+//!
+//! The EGM96 geoid on a 15' grid (`egm96-15.pgm`, about 2 MB) is bundled as [`EGM96_15_PGM`] and
+//! used by [`Geoid::default()`], [`Point::msl()`](crate::base::Point::msl) and
+//! [`Point::with_msl()`](crate::base::Point::with_msl). Interpolating it is accurate to within a
+//! few decimeters in most places, and about a meter in rugged terrain:
+//!
+//! ```rust
+//! # use cot_proto::base::Point;
+//! let point = Point {
+//!     hae: 100.0,
+//!     ..Point::from_lat_lon(38.6281550, -90.2208450)
+//! };
+//! // The geoid is 31.6 m below the ellipsoid here.
+//! assert!((point.msl().unwrap() - 131.6).abs() < 0.1);
+//! ```
+//!
+//! For more accuracy, load one of the finer GeographicLib geoid grids (e.g. `egm96-5.pgm`, or
+//! `egm2008-1.pgm` for a more recent model) with [`Geoid::from_pgm()`], and install it with
+//! [`set_default()`] before the first conversion:
+//!
+//! ```rust,ignore
+//! # use cot_proto::geoid::{self, Geoid};
+//! static EGM2008: &[u8] = include_bytes!("egm2008-5.pgm");
+//! geoid::set_default(Geoid::from_pgm(EGM2008)?);
+//! ```
+
+use std::io::{BufRead, BufReader, Read};
+use std::sync::OnceLock;
+
+use crate::Error;
+
+/// The EGM96 geoid on a 15' grid, in GeographicLib's PGM format.
+pub const EGM96_15_PGM: &[u8] = include_bytes!("geoid/egm96-15.pgm");
+
+static DEFAULT_GEOID: OnceLock<Geoid> = OnceLock::new();
+
+/// Install the geoid used by [`Point::msl()`](crate::base::Point::msl) and
+/// [`Point::with_msl()`](crate::base::Point::with_msl), in place of the bundled EGM96. Returns the
+/// geoid back if [`default_geoid()`] was already installed or used.
+pub fn set_default(geoid: Geoid) -> Result<(), Geoid> {
+    DEFAULT_GEOID.set(geoid)
+}
+
+/// The geoid installed with [`set_default()`], or the bundled EGM96.
+pub fn default_geoid() -> &'static Geoid {
+    DEFAULT_GEOID.get_or_init(Geoid::default)
+}
+
+/// A global grid of geoid heights, interpolated bilinearly.
+///
+/// Rows run from north (90°) to south (-90°), and columns east from longitude 0 to
+/// `360 - spacing`, wrapping around.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Geoid {
+    /// Grid spacing in degrees, the same for latitude and longitude.
+    spacing: f64,
+    width: usize,
+    height: usize,
+    /// Geoid heights in meters, row-major.
+    heights: Vec<f32>,
+}
+
+impl Default for Geoid {
+    /// The bundled EGM96 15' grid.
+    fn default() -> Self {
+        Self::from_pgm(EGM96_15_PGM).expect("bundled EGM96 grid is valid")
+    }
+}
+
+impl Geoid {
+    /// Create a geoid from a grid with `spacing` degrees between nodes, starting at 90°N 0°E.
+    /// `heights` must hold `360 / spacing` columns by `180 / spacing + 1` rows.
+    pub fn new(spacing: f64, heights: Vec<f32>) -> Result<Self, Error> {
+        let width = 360.0 / spacing;
+        if spacing <= 0.0 || width.fract() != 0.0 || (180.0 / spacing).fract() != 0.0 {
+            return Err(Error::BadField(
+                "geoid spacing must divide 180 degrees".into(),
+            ));
+        }
+        let width = width as usize;
+        let height = (180.0 / spacing) as usize + 1;
+        if heights.len() != width * height {
+            return Err(Error::BadField(
+                "geoid grid size doesn't match its spacing".into(),
+            ));
+        }
+        Ok(Self {
+            spacing,
+            width,
+            height,
+            heights,
+        })
+    }
+
+    /// Read a geoid grid in the 16-bit PGM format of GeographicLib's geoid data files. The
+    /// `# Offset` and `# Scale` header comments map pixel values to meters.
+    pub fn from_pgm<R: Read>(reader: R) -> Result<Self, Error> {
+        let mut reader = BufReader::new(reader);
+        let mut offset = None;
+        let mut scale = None;
+        let mut fields = vec![];
+        let mut line = String::new();
+        // Header: magic, width, height and maxval, with comment lines in between.
+        while fields.len() < 4 {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                return Err(Error::BadField("truncated geoid PGM header".into()));
+            }
+            if let Some(comment) = line.strip_prefix('#') {
+                let mut words = comment.split_whitespace();
+                match (words.next(), words.next().map(str::parse::<f64>)) {
+                    (Some("Offset"), Some(Ok(v))) => offset = Some(v),
+                    (Some("Scale"), Some(Ok(v))) => scale = Some(v),
+                    _ => {}
+                }
+                continue;
+            }
+            fields.extend(line.split_whitespace().map(str::to_string));
+        }
+        if fields[0] != "P5" || fields[3] != "65535" {
+            return Err(Error::BadField(
+                "geoid PGM must be 16-bit binary (P5)".into(),
+            ));
+        }
+        let (Some(offset), Some(scale)) = (offset, scale) else {
+            return Err(Error::BadField("geoid PGM has no Offset or Scale".into()));
+        };
+        let width: usize = fields[1]
+            .parse()
+            .map_err(|_| Error::BadField("invalid geoid PGM width".into()))?;
+        let height: usize = fields[2]
+            .parse()
+            .map_err(|_| Error::BadField("invalid geoid PGM height".into()))?;
+        if width == 0 || height < 2 || width != 2 * (height - 1) {
+            return Err(Error::BadField("geoid PGM doesn't cover the globe".into()));
+        }
+
+        let mut data = vec![0; width * height * 2];
+        reader.read_exact(&mut data)?;
+        let heights = data
+            .chunks_exact(2)
+            .map(|b| (offset + scale * u16::from_be_bytes([b[0], b[1]]) as f64) as f32)
+            .collect();
+        Self::new(360.0 / width as f64, heights)
+    }
+
+    /// Grid spacing in degrees.
+    pub fn spacing(&self) -> f64 {
+        self.spacing
+    }
+
+    /// Geoid height `N` in meters at the given position: the height of the geoid (MSL) above the
+    /// WGS-84 ellipsoid.
+    pub fn height(&self, lat: f64, lon: f64) -> f64 {
+        let y = ((90.0 - lat.clamp(-90.0, 90.0)) / self.spacing).min((self.height - 1) as f64);
+        let x = lon.rem_euclid(360.0) / self.spacing;
+        let row = (y.floor() as usize).min(self.height - 2);
+        let col = x.floor() as usize % self.width;
+        let (fy, fx) = (y - row as f64, x - x.floor());
+
+        let at = |r: usize, c: usize| self.heights[r * self.width + c % self.width] as f64;
+        let north = at(row, col) * (1.0 - fx) + at(row, col + 1) * fx;
+        let south = at(row + 1, col) * (1.0 - fx) + at(row + 1, col + 1) * fx;
+        north * (1.0 - fy) + south * fy
+    }
+
+    /// Height above mean sea level for a height above ellipsoid.
+    pub fn hae_to_msl(&self, lat: f64, lon: f64, hae: f64) -> f64 {
+        hae - self.height(lat, lon)
+    }
+
+    /// Height above ellipsoid for a height above mean sea level.
+    pub fn msl_to_hae(&self, lat: f64, lon: f64, msl: f64) -> f64 {
+        msl + self.height(lat, lon)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// 90 degree grid (4 x 3) in GeographicLib's PGM format, heights in meters are
+    /// `-108 + 0.5 * value`.
+    fn test_pgm(values: &[u16]) -> Vec<u8> {
+        let mut pgm = b"P5\n# Geoid file test\n# Offset -108\n# Scale 0.5\n4 3\n65535\n".to_vec();
+        for v in values {
+            pgm.extend_from_slice(&v.to_be_bytes());
+        }
+        pgm
+    }
+
+    #[test]
+    fn test_geoid_pgm_interpolation() {
+        #[rustfmt::skip]
+        let pgm = test_pgm(&[
+            216, 216, 216, 216,
+            216, 236, 256, 276,
+            116, 116, 116, 116,
+        ]);
+        let geoid = Geoid::from_pgm(&pgm[..]).unwrap();
+        assert_eq!(geoid.spacing(), 90.0);
+        assert_eq!(geoid.height(90.0, 123.0), 0.0);
+        assert_eq!(geoid.height(-90.0, 0.0), -50.0);
+        assert_eq!(geoid.height(0.0, 90.0), 10.0);
+        assert_eq!(geoid.height(0.0, 45.0), 5.0);
+        assert_eq!(geoid.height(45.0, 90.0), 5.0);
+        // Wraps from 270 back to 0
+        assert_eq!(geoid.height(0.0, 315.0), 15.0);
+        assert_eq!(geoid.height(0.0, -45.0), 15.0);
+
+        assert_eq!(geoid.hae_to_msl(0.0, 180.0, 100.0), 80.0);
+        assert_eq!(geoid.msl_to_hae(0.0, 180.0, 80.0), 100.0);
+    }
+
+    #[test]
+    fn test_egm96() {
+        let geoid = Geoid::default();
+        assert_eq!(geoid.spacing(), 0.25);
+        // Test values from NGA's EGM96 calculator: lat, lon and geoid height (m).
+        for [lat, lon, n] in [
+            [38.6281550, 269.7791550, -31.628],
+            [-14.6212170, 305.0211140, -2.969],
+            [46.8743190, 102.4487290, -43.575],
+            [-23.6174460, 133.8747120, 15.871],
+            [38.6254730, 359.9995000, 50.066],
+            [-0.4667440, 0.0023000, 17.329],
+        ] {
+            let height = geoid.height(lat, lon);
+            assert!((height - n).abs() < 0.1, "{} {}: {}", lat, lon, height);
+        }
+        assert!((geoid.height(38.6281550, -90.2208450) + 31.628).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_point_msl() {
+        use crate::base::Point;
+
+        let geoid = default_geoid();
+        assert!(set_default(Geoid::new(90.0, vec![30.0; 12]).unwrap()).is_err());
+        let drone = Point {
+            hae: 100.0,
+            ..Point::from_lat_lon(-34.9, 138.6)
+        };
+        let msl = drone.msl().unwrap();
+        assert_eq!(msl, (100.0 - geoid.height(-34.9, 138.6)) as f32);
+        assert_eq!(drone.with_msl(msl).hae, 100.0);
+        assert_eq!(Point::from_lat_lon(0.0, 0.0).msl(), None);
+    }
+
+    #[test]
+    fn test_geoid_invalid() {
+        assert!(Geoid::new(90.0, vec![0.0; 11]).is_err());
+        assert!(Geoid::new(7.0, vec![]).is_err());
+        let truncated = test_pgm(&[0; 11]);
+        assert!(Geoid::from_pgm(&truncated[..]).is_err());
+        let pgm = b"P5\n4 3\n65535\n".to_vec();
+        assert!(Geoid::from_pgm(&pgm[..]).is_err());
+    }
+}
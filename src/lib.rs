@@ -9,18 +9,40 @@
 //! analogous to [serde_json::Value](https://docs.rs/serde_json/latest/serde_json/enum.Value.html),
 //! but I haven't found anything similar for XML.
 //!
+//! For messages from sources which send malformed detail elements, [detail::parse_lenient()]
+//! skips them and reports what was skipped.
+//!
+//! [rules::check()] checks messages against a subset of the CoT and ATAK schema rules, e.g. before
+//! sending them to receivers with strict parsers.
+//!
+//! [writer::CotWriter] writes messages with schema element order, and optionally in the style of
+//! ATAK.
+//!
+
+use std::borrow::Cow;
+use std::fmt;
 
 use thiserror::Error;
+
 pub mod base;
+pub mod coords;
 pub mod detail;
+#[cfg(feature = "dted")]
+pub mod dted;
 pub mod examples;
+#[cfg(feature = "geoid")]
+pub mod geoid;
+pub mod magnetic;
+pub mod rules;
 #[cfg(feature = "tak")]
 pub mod tak;
+pub mod time;
+pub mod writer;
 
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("message field error: {0}")]
-    BadField(&'static str),
+    BadField(Cow<'static, str>),
 
     #[error(transparent)]
     IoError(#[from] std::io::Error),
@@ -30,6 +52,90 @@ pub enum Error {
     Xml(#[from] quick_xml::errors::Error),
     #[error(transparent)]
     De(#[from] quick_xml::de::DeError),
+    #[error(transparent)]
+    Se(#[from] quick_xml::se::SeError),
+    #[cfg(feature = "iconset")]
+    #[error(transparent)]
+    Zip(#[from] zip::result::ZipError),
+
+    /// An error at a known place in the input, e.g. from [base::from_str()].
+    #[error("{}: {source}", location(path, position, value))]
+    Located {
+        /// Path of the element or attribute, e.g. `event/detail/contact/@phone`.
+        path: String,
+        position: Option<Position>,
+        /// The offending attribute value or text, if known.
+        value: Option<String>,
+        source: Box<Error>,
+    },
+}
+
+fn location(path: &str, position: &Option<Position>, value: &Option<String>) -> String {
+    let mut location = path.to_string();
+    if let Some(position) = position {
+        location += &format!(" at {}", position);
+    }
+    if let Some(value) = value {
+        location += &format!(" (value {:?})", value);
+    }
+    location
+}
+
+impl Error {
+    /// The error without any location, i.e. the source of an [`Error::Located`].
+    pub fn root_cause(&self) -> &Error {
+        match self {
+            Error::Located { source, .. } => source.root_cause(),
+            e => e,
+        }
+    }
+
+    /// Position of the error in the input, if known.
+    pub fn position(&self) -> Option<Position> {
+        match self {
+            Error::Located { position, .. } => *position,
+            _ => None,
+        }
+    }
+
+    /// Path of the element or attribute where the error is, if known.
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            Error::Located { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+}
+
+/// A position in XML input text.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Position {
+    /// Byte offset.
+    pub offset: usize,
+    /// Line number, starting at 1.
+    pub line: usize,
+    /// Column number in characters, starting at 1.
+    pub column: usize,
+}
+
+impl Position {
+    /// Position of byte `offset` in `input`.
+    pub fn new(input: &str, offset: usize) -> Self {
+        let offset = offset.min(input.len());
+        let before = input.get(..offset).unwrap_or_default();
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Self {
+            offset,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,392 @@
+//! Magnetic declination from the World Magnetic Model (WMM), for converting between magnetic and
+//! true bearings.
+//!
+//! The WMM2025 coefficients are embedded and used by [`MagneticModel::default()`]. WMM2025 is
+//! valid from 2025.0 to 2030.0, and the secular variation terms are extrapolated outside that
+//! range, losing accuracy. The WMM2020 coefficients are embedded as well, for earlier dates.
+//!
+//! [`Point::declination()`](crate::base::Point::declination) and the bearing conversions of the
+//! `tak` details use the model returned by [`model_for()`]: WMM2020 before 2025.0 and WMM2025
+//! after. Install a later model, loaded from NOAA's `WMM.COF` file with
+//! [`MagneticModel::from_cof()`], with [`set_default()`]; it is then used for all dates.
+//!
+//! ```rust
+//! # use cot_proto::magnetic::MagneticModel;
+//! let wmm = MagneticModel::default();
+//! let field = wmm.field(-80.0, 240.0, 0.0, 2025.0);
+//! assert!((field.declination() - 68.78).abs() < 0.01);
+//! ```
+
+use std::sync::OnceLock;
+
+use chrono::{DateTime, Datelike, NaiveDate, Timelike, Utc};
+
+use crate::Error;
+
+/// Coefficients of the WMM2020 model, in NOAA's `WMM.COF` format.
+pub const WMM2020_COF: &str = include_str!("magnetic/WMM2020.COF");
+
+/// Coefficients of the WMM2025 model, in NOAA's `WMM.COF` format.
+pub const WMM2025_COF: &str = include_str!("magnetic/WMM2025.COF");
+
+const WGS84_A: f64 = 6_378_137.0;
+const WGS84_F: f64 = 1.0 / 298.257_223_563;
+/// Geomagnetic reference radius of the WMM, in meters.
+const REFERENCE_RADIUS: f64 = 6_371_200.0;
+
+/// Model used when none is installed: the embedded models, chosen by date.
+#[derive(Debug)]
+enum DefaultModel {
+    Installed(MagneticModel),
+    Embedded,
+}
+
+static DEFAULT_MODEL: OnceLock<DefaultModel> = OnceLock::new();
+static WMM2020: OnceLock<MagneticModel> = OnceLock::new();
+static WMM2025: OnceLock<MagneticModel> = OnceLock::new();
+
+/// Install the model used by [`Point::declination()`](crate::base::Point::declination), in place
+/// of the embedded models. Returns the model back if a model was already installed, or the
+/// default was already used.
+#[allow(clippy::result_large_err)]
+pub fn set_default(model: MagneticModel) -> Result<(), MagneticModel> {
+    DEFAULT_MODEL
+        .set(DefaultModel::Installed(model))
+        .map_err(|default| match default {
+            DefaultModel::Installed(model) => model,
+            DefaultModel::Embedded => unreachable!("only installed models are set"),
+        })
+}
+
+/// The model installed with [`set_default()`], or the embedded WMM2025.
+pub fn default_model() -> &'static MagneticModel {
+    model_for(f64::INFINITY)
+}
+
+/// The model to use at decimal `year`: the model installed with [`set_default()`], or else the
+/// embedded WMM2020 before 2025.0 and WMM2025 from then on.
+pub fn model_for(year: f64) -> &'static MagneticModel {
+    match DEFAULT_MODEL.get_or_init(|| DefaultModel::Embedded) {
+        DefaultModel::Installed(model) => model,
+        DefaultModel::Embedded if year < 2025.0 => WMM2020.get_or_init(|| {
+            MagneticModel::from_cof(WMM2020_COF).expect("embedded WMM2020 coefficients are valid")
+        }),
+        DefaultModel::Embedded => WMM2025.get_or_init(MagneticModel::default),
+    }
+}
+
+/// A spherical harmonic model of the main geomagnetic field, such as the WMM.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MagneticModel {
+    /// Model name, e.g. `WMM-2025`.
+    pub name: String,
+    /// Decimal year the main field coefficients are for.
+    pub epoch: f64,
+    degree: usize,
+    /// Gauss coefficients `g`, `h` in nT and their secular variation `g_dot`, `h_dot` in nT/year,
+    /// indexed by [`index()`].
+    g: Vec<f64>,
+    h: Vec<f64>,
+    g_dot: Vec<f64>,
+    h_dot: Vec<f64>,
+}
+
+/// Index of the degree `n`, order `m` coefficient.
+fn index(n: usize, m: usize) -> usize {
+    n * (n + 1) / 2 + m
+}
+
+impl Default for MagneticModel {
+    /// The embedded WMM2025 model.
+    fn default() -> Self {
+        Self::from_cof(WMM2025_COF).expect("embedded WMM2025 coefficients are valid")
+    }
+}
+
+impl MagneticModel {
+    /// Parse a model in NOAA's `.COF` coefficient file format: a header line with the epoch and
+    /// model name, then `n m g h g_dot h_dot` lines, ending with a line of `9`s.
+    pub fn from_cof(text: &str) -> Result<Self, Error> {
+        let mut lines = text.lines().filter(|l| !l.trim().is_empty());
+        let mut header = lines
+            .next()
+            .ok_or(Error::BadField("empty magnetic model".into()))?
+            .split_whitespace();
+        let epoch = header
+            .next()
+            .and_then(|e| e.parse().ok())
+            .ok_or(Error::BadField("invalid magnetic model epoch".into()))?;
+        let name = header.next().unwrap_or_default().to_string();
+
+        let mut terms = vec![];
+        for line in lines {
+            if line.starts_with("9999") {
+                break;
+            }
+            let fields: Vec<f64> = line
+                .split_whitespace()
+                .map(str::parse)
+                .collect::<Result<_, _>>()
+                .map_err(|_| Error::BadField("invalid magnetic model coefficient".into()))?;
+            let [n, m, g, h, g_dot, h_dot] = fields[..] else {
+                return Err(Error::BadField(
+                    "magnetic model line must have 6 fields".into(),
+                ));
+            };
+            if n < 1.0 || m < 0.0 || m > n || n.fract() != 0.0 || m.fract() != 0.0 {
+                return Err(Error::BadField(
+                    "invalid magnetic model degree or order".into(),
+                ));
+            }
+            terms.push((n as usize, m as usize, [g, h, g_dot, h_dot]));
+        }
+        let degree = terms
+            .iter()
+            .map(|(n, _, _)| *n)
+            .max()
+            .ok_or(Error::BadField("magnetic model has no coefficients".into()))?;
+
+        let size = index(degree, degree) + 1;
+        let mut model = Self {
+            name,
+            epoch,
+            degree,
+            g: vec![0.0; size],
+            h: vec![0.0; size],
+            g_dot: vec![0.0; size],
+            h_dot: vec![0.0; size],
+        };
+        for (n, m, [g, h, g_dot, h_dot]) in terms {
+            let i = index(n, m);
+            model.g[i] = g;
+            model.h[i] = h;
+            model.g_dot[i] = g_dot;
+            model.h_dot[i] = h_dot;
+        }
+        Ok(model)
+    }
+
+    /// Maximum degree of the spherical harmonic expansion, 12 for the WMM.
+    pub fn degree(&self) -> usize {
+        self.degree
+    }
+
+    /// Magnetic field at geodetic latitude and longitude in degrees, `height` in meters above the
+    /// WGS-84 ellipsoid, at decimal `year` (see [`decimal_year()`]).
+    pub fn field(&self, lat: f64, lon: f64, height: f64, year: f64) -> MagneticField {
+        let dt = year - self.epoch;
+        let phi = lat.to_radians();
+        let lambda = lon.to_radians();
+
+        // Geodetic to geocentric spherical coordinates.
+        let e2 = WGS84_F * (2.0 - WGS84_F);
+        let (sin_phi, cos_phi) = phi.sin_cos();
+        let rc = WGS84_A / (1.0 - e2 * sin_phi * sin_phi).sqrt();
+        let p = (rc + height) * cos_phi;
+        let z = (rc * (1.0 - e2) + height) * sin_phi;
+        let r = p.hypot(z);
+        let phi_c = (z / r).asin();
+
+        // Schmidt semi-normalized associated Legendre functions of sin(phi_c) and their
+        // derivatives with respect to colatitude.
+        let (x, s) = phi_c.sin_cos();
+        let size = index(self.degree, self.degree) + 1;
+        let mut pnm = vec![0.0; size];
+        let mut dpnm = vec![0.0; size];
+        pnm[0] = 1.0;
+        for n in 1..=self.degree {
+            for m in 0..=n {
+                let i = index(n, m);
+                if n == m {
+                    let k = if n == 1 {
+                        1.0
+                    } else {
+                        ((2 * n - 1) as f64 / (2 * n) as f64).sqrt()
+                    };
+                    let j = index(n - 1, n - 1);
+                    pnm[i] = k * s * pnm[j];
+                    dpnm[i] = k * (x * pnm[j] + s * dpnm[j]);
+                } else {
+                    let k = ((n * n - m * m) as f64).sqrt();
+                    let j = index(n - 1, m);
+                    let mut p = (2 * n - 1) as f64 * x * pnm[j];
+                    let mut dp = (2 * n - 1) as f64 * (x * dpnm[j] - s * pnm[j]);
+                    if n >= 2 && m + 2 <= n {
+                        let k2 = (((n - 1) * (n - 1) - m * m) as f64).sqrt();
+                        let j2 = index(n - 2, m);
+                        p -= k2 * pnm[j2];
+                        dp -= k2 * dpnm[j2];
+                    }
+                    pnm[i] = p / k;
+                    dpnm[i] = dp / k;
+                }
+            }
+        }
+
+        // Field in geocentric north, east and down components.
+        let (mut bx, mut by, mut bz) = (0.0, 0.0, 0.0);
+        let ratio = REFERENCE_RADIUS / r;
+        let mut scale = ratio * ratio;
+        for n in 1..=self.degree {
+            scale *= ratio;
+            for m in 0..=n {
+                let i = index(n, m);
+                let g = self.g[i] + dt * self.g_dot[i];
+                let h = self.h[i] + dt * self.h_dot[i];
+                let (sin_ml, cos_ml) = (m as f64 * lambda).sin_cos();
+                let gh = g * cos_ml + h * sin_ml;
+                bx += scale * gh * dpnm[i];
+                by += scale * m as f64 * (g * sin_ml - h * cos_ml) * pnm[i];
+                bz -= scale * (n + 1) as f64 * gh * pnm[i];
+            }
+        }
+        // The east component is undefined at the poles.
+        by /= s.max(1e-12);
+
+        // Rotate to the geodetic frame.
+        let (sin_d, cos_d) = (phi_c - phi).sin_cos();
+        MagneticField {
+            x: bx * cos_d - bz * sin_d,
+            y: by,
+            z: bx * sin_d + bz * cos_d,
+        }
+    }
+
+    /// Magnetic declination in degrees, positive east of true north. See
+    /// [`MagneticModel::field()`].
+    pub fn declination(&self, lat: f64, lon: f64, height: f64, year: f64) -> f64 {
+        self.field(lat, lon, height, year).declination()
+    }
+}
+
+/// Magnetic field vector in nanotesla, in the local geodetic frame.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MagneticField {
+    /// North component.
+    pub x: f64,
+    /// East component.
+    pub y: f64,
+    /// Down component.
+    pub z: f64,
+}
+
+impl MagneticField {
+    /// Declination in degrees, the angle from true north to the horizontal field, positive east.
+    pub fn declination(&self) -> f64 {
+        self.y.atan2(self.x).to_degrees()
+    }
+
+    /// Inclination (dip) in degrees, positive down.
+    pub fn inclination(&self) -> f64 {
+        self.z.atan2(self.horizontal_intensity()).to_degrees()
+    }
+
+    pub fn horizontal_intensity(&self) -> f64 {
+        self.x.hypot(self.y)
+    }
+
+    pub fn total_intensity(&self) -> f64 {
+        self.horizontal_intensity().hypot(self.z)
+    }
+}
+
+/// Time as a decimal year, e.g. 2020.5 for the start of July 2nd 2020, as used by magnetic models.
+pub fn decimal_year(time: &DateTime<Utc>) -> f64 {
+    let year = time.year();
+    let days_in_year = match NaiveDate::from_ymd_opt(year, 2, 29) {
+        Some(_) => 366.0,
+        None => 365.0,
+    };
+    let day = time.ordinal0() as f64 + time.num_seconds_from_midnight() as f64 / 86_400.0;
+    year as f64 + day / days_in_year
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Test values from the WMM2020 and WMM2025 reports: year, height (km), lat, lon, X, Y, Z (nT)
+    /// and declination (degrees).
+    #[rustfmt::skip]
+    const WMM2020_TEST_VALUES: [[f64; 8]; 6] = [
+        [2020.0, 0.0, 80.0, 0.0, 6570.4, -146.3, 54606.0, -1.28],
+        [2020.0, 0.0, 0.0, 120.0, 39624.3, 109.9, -10932.5, 0.16],
+        [2020.0, 0.0, -80.0, 240.0, 5940.6, 15772.1, -52480.8, 69.36],
+        [2020.0, 100.0, 80.0, 0.0, 6261.8, -185.5, 52429.1, -1.70],
+        [2020.0, 100.0, 0.0, 120.0, 37636.7, 104.9, -10474.8, 0.16],
+        [2020.0, 100.0, -80.0, 240.0, 5744.9, 14799.5, -49969.4, 68.78],
+    ];
+    #[rustfmt::skip]
+    const WMM2025_TEST_VALUES: [[f64; 8]; 6] = [
+        [2025.0, 0.0, 80.0, 0.0, 6521.6, 145.9, 54791.5, 1.28],
+        [2025.0, 0.0, 0.0, 120.0, 39677.8, -109.6, -10580.2, -0.16],
+        [2025.0, 0.0, -80.0, 240.0, 6117.5, 15751.9, -52022.5, 68.78],
+        [2027.5, 100.0, 80.0, 0.0, 6196.7, 233.8, 52670.5, 2.16],
+        [2027.5, 100.0, 0.0, 120.0, 37711.5, -148.7, -9969.8, -0.23],
+        [2027.5, 100.0, -80.0, 240.0, 5984.0, 14760.1, -49317.7, 67.93],
+    ];
+
+    fn check(wmm: &MagneticModel, values: &[[f64; 8]]) {
+        assert_eq!(wmm.degree(), 12);
+        for &[year, height, lat, lon, x, y, z, declination] in values {
+            let field = wmm.field(lat, lon, height * 1000.0, year);
+            assert!((field.x - x).abs() < 0.1, "{:?} {}", field, x);
+            assert!((field.y - y).abs() < 0.1, "{:?} {}", field, y);
+            assert!((field.z - z).abs() < 0.1, "{:?} {}", field, z);
+            assert!(
+                (field.declination() - declination).abs() < 0.01,
+                "{:?}",
+                field
+            );
+        }
+    }
+
+    #[test]
+    fn test_wmm2020() {
+        let wmm = MagneticModel::from_cof(WMM2020_COF).unwrap();
+        assert_eq!(wmm.name, "WMM-2020");
+        assert_eq!(wmm.epoch, 2020.0);
+        check(&wmm, &WMM2020_TEST_VALUES);
+        let field = wmm.field(80.0, 0.0, 0.0, 2020.0);
+        assert!((field.inclination() - 83.14).abs() < 0.01);
+        assert!((field.total_intensity() - 55000.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_wmm2025() {
+        let wmm = MagneticModel::default();
+        assert_eq!(wmm.name, "WMM-2025");
+        assert_eq!(wmm.epoch, 2025.0);
+        check(&wmm, &WMM2025_TEST_VALUES);
+        let field = wmm.field(80.0, 0.0, 0.0, 2025.0);
+        assert!((field.inclination() - 83.21).abs() < 0.01);
+        assert!((field.total_intensity() - 55178.5).abs() < 0.5);
+        assert_eq!(default_model(), &wmm);
+    }
+
+    #[test]
+    fn test_model_for() {
+        assert_eq!(model_for(2022.5).name, "WMM-2020");
+        assert_eq!(model_for(2025.0).name, "WMM-2025");
+        assert_eq!(model_for(2031.0).name, "WMM-2025");
+        let point = crate::base::Point::from_lat_lon(-80.0, -120.0);
+        let declination = point.declination(&"2020-01-01T00:00:00Z".parse().unwrap());
+        assert!((declination - 69.36).abs() < 0.01, "{}", declination);
+    }
+
+    #[test]
+    fn test_decimal_year() {
+        let time = "2020-07-02T00:00:00Z".parse().unwrap();
+        assert_eq!(decimal_year(&time), 2020.5);
+        let time = "2021-01-01T00:00:00Z".parse().unwrap();
+        assert_eq!(decimal_year(&time), 2021.0);
+    }
+
+    #[test]
+    fn test_invalid_cof() {
+        assert!(MagneticModel::from_cof("").is_err());
+        assert!(MagneticModel::from_cof("2025.0 WMM-2025 11/13/2024\n").is_err());
+        assert!(MagneticModel::from_cof("2025.0 WMM-2025\n 1 2 0 0 0 0\n").is_err());
+        assert!(MagneticModel::from_cof("2025.0 WMM-2025\n 1 0 -29351.8 0.0\n").is_err());
+    }
+}
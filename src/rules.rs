@@ -0,0 +1,792 @@
+//! CoT rule checks: a subset of the rules of the CoT and ATAK schemas, checked without the schemas.
+//!
+//! This is not XSD validation. The rules are transcribed by hand from parts of the MITRE CoT XSDs
+//! (the `event` base schema and the `contact`, `link`, `remarks`, `track` and `uid` detail
+//! sub-schemas) and, with the `tak` feature, the ATAK marker schemas (`__group`, `takv`, `status`,
+//! `precisionlocation`, `usericon` and the shape style elements). Other detail elements, and some
+//! constraints of the covered ones, are not checked, so a message without violations may still be
+//! invalid against the XSDs. The rules are built in, so checking needs no schema files or network
+//! access.
+//!
+//! [`check()`] checks required attributes, attribute types and ranges, how many times elements
+//! may occur, and that `<point>` comes before `<detail>` in `<event>`. Unknown detail elements,
+//! and unknown attributes of detail elements, are allowed, as the schemas allow extensions there.
+//!
+//! ```rust
+//! # use cot_proto::examples::COT_TRACK_EXAMPLE;
+//! # use cot_proto::rules::check;
+//! let input = COT_TRACK_EXAMPLE.replace("lat=\"-23.14187321890312\"", "lat=\"-123.1\"");
+//! let violations = check(&input).unwrap();
+//! assert_eq!(violations.len(), 1);
+//! assert_eq!(violations[0].path, "event/point/@lat");
+//! assert_eq!(violations[0].position.line, 4);
+//! ```
+
+use std::fmt;
+
+use chrono::NaiveDate;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+use crate::base::{find_attr, locate_error};
+use crate::{location, Error, Position};
+
+/// A rule broken by a CoT message.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Violation {
+    /// Path of the element or attribute, e.g. `event/detail/track/@course`.
+    pub path: String,
+    pub position: Position,
+    /// The offending attribute value, if any.
+    pub value: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {}",
+            location(&self.path, &Some(self.position), &self.value),
+            self.message
+        )
+    }
+}
+
+impl From<Violation> for Error {
+    fn from(violation: Violation) -> Self {
+        Error::Located {
+            path: violation.path,
+            position: Some(violation.position),
+            value: violation.value,
+            source: Box::new(Error::BadField(violation.message.into())),
+        }
+    }
+}
+
+/// Check the CoT message `input` against the built-in rules. Returns the violations found, in
+/// document order, or an error if `input` isn't well-formed XML.
+pub fn check(input: &str) -> Result<Vec<Violation>, Error> {
+    let mut violations = vec![];
+    // Open elements: path, start offset, rule (if known), number of each known child, and index
+    // of the last known child.
+    #[allow(clippy::type_complexity)]
+    let mut stack: Vec<(
+        String,
+        usize,
+        Option<&'static ElementRule>,
+        Vec<usize>,
+        usize,
+    )> = vec![];
+    let mut root_seen = false;
+    let mut reader = Reader::from_str(input);
+    loop {
+        let offset = reader.buffer_position() as usize;
+        let event = reader
+            .read_event()
+            .map_err(|e| locate_error(input, Error::Xml(e)))?;
+        let (start, is_empty) = match event {
+            Event::Start(start) => (start, false),
+            Event::Empty(start) => (start, true),
+            Event::End(_) => {
+                if let Some((path, offset, rule, counts, _)) = stack.pop() {
+                    check_counts(input, &path, offset, rule, &counts, &mut violations);
+                }
+                continue;
+            }
+            Event::Eof => break,
+            _ => continue,
+        };
+        let name = String::from_utf8_lossy(start.name().as_ref()).to_string();
+        let violation = |path: String, message: String| Violation {
+            position: Position::new(input, offset),
+            path,
+            value: None,
+            message,
+        };
+
+        let (path, rule) = match stack.last_mut() {
+            None if root_seen => {
+                violations.push(violation(name.clone(), "more than one root element".into()));
+                (name, None)
+            }
+            None => {
+                root_seen = true;
+                if name != EVENT.name {
+                    violations.push(violation(name.clone(), "expected <event>".into()));
+                }
+                (name.clone(), (name == EVENT.name).then_some(&EVENT))
+            }
+            Some((parent_path, _, parent_rule, counts, last)) => {
+                let path = format!("{}/{}", parent_path, name);
+                let child = parent_rule.and_then(|parent| {
+                    let mut children = parent.children().enumerate();
+                    let (i, child) = children.find(|(_, c)| c.rule.name == name)?;
+                    Some((parent, i, child))
+                });
+                match (child, *parent_rule) {
+                    (Some((parent, i, child)), _) => {
+                        if parent.sequence && i < *last {
+                            let after = parent.children().nth(*last).map_or("", |c| c.rule.name);
+                            violations.push(violation(
+                                path.clone(),
+                                format!("<{}> must come before <{}>", name, after),
+                            ));
+                        }
+                        *last = (*last).max(i);
+                        counts[i] += 1;
+                        if child.max.is_some_and(|max| counts[i] > max) {
+                            violations.push(violation(
+                                path.clone(),
+                                format!("more than {} <{}>", child.max.unwrap_or_default(), name),
+                            ));
+                        }
+                        (path, Some(child.rule))
+                    }
+                    (None, Some(parent)) if !parent.open => {
+                        violations.push(violation(
+                            path.clone(),
+                            format!("unexpected element in <{}>", parent.name),
+                        ));
+                        (path, None)
+                    }
+                    (None, _) => (path, None),
+                }
+            }
+        };
+
+        if let Some(rule) = rule {
+            check_attributes(input, offset, &start, &path, rule, &mut violations)?;
+        }
+        let counts = vec![0; rule.map_or(0, |r| r.children().count())];
+        if is_empty {
+            check_counts(input, &path, offset, rule, &counts, &mut violations);
+        } else {
+            stack.push((path, offset, rule, counts, 0));
+        }
+    }
+    if !root_seen {
+        violations.push(Violation {
+            path: String::new(),
+            position: Position::new(input, input.len()),
+            value: None,
+            message: "missing <event>".into(),
+        });
+    }
+    Ok(violations)
+}
+
+fn check_attributes(
+    input: &str,
+    offset: usize,
+    start: &BytesStart,
+    path: &str,
+    rule: &ElementRule,
+    violations: &mut Vec<Violation>,
+) -> Result<(), Error> {
+    for attr in start.attributes() {
+        let attr = attr.map_err(|e| locate_error(input, e.into()))?;
+        let name = String::from_utf8_lossy(attr.key.as_ref()).to_string();
+        let (attr_offset, value) = find_attr(input, offset, start, &name)
+            .unwrap_or_else(|| (offset, String::from_utf8_lossy(&attr.value).to_string()));
+        let message = match rule.attributes.iter().find(|a| a.name == name) {
+            Some(attr_rule) => attr_rule.kind.check(&value),
+            None if rule.open => None,
+            None => Some(format!("unexpected attribute of <{}>", rule.name)),
+        };
+        if let Some(message) = message {
+            violations.push(Violation {
+                path: format!("{}/@{}", path, name),
+                position: Position::new(input, attr_offset),
+                value: Some(value),
+                message,
+            });
+        }
+    }
+
+    let has = |name: &str| {
+        start
+            .attributes()
+            .flatten()
+            .any(|a| a.key.as_ref() == name.as_bytes())
+    };
+    for attr_rule in rule.attributes {
+        let required = match attr_rule.usage {
+            Use::Optional => false,
+            Use::Required => true,
+            Use::RequiredUnless(others) => !others.iter().any(|other| has(other)),
+        };
+        if required && !has(attr_rule.name) {
+            violations.push(Violation {
+                path: format!("{}/@{}", path, attr_rule.name),
+                position: Position::new(input, offset),
+                value: None,
+                message: format!("missing required attribute of <{}>", rule.name),
+            });
+        }
+    }
+    Ok(())
+}
+
+fn check_counts(
+    input: &str,
+    path: &str,
+    offset: usize,
+    rule: Option<&ElementRule>,
+    counts: &[usize],
+    violations: &mut Vec<Violation>,
+) {
+    let Some(rule) = rule else {
+        return;
+    };
+    for (child, count) in rule.children().zip(counts) {
+        if *count < child.min {
+            violations.push(Violation {
+                path: format!("{}/{}", path, child.rule.name),
+                position: Position::new(input, offset),
+                value: None,
+                message: format!("missing <{}> in <{}>", child.rule.name, rule.name),
+            });
+        }
+    }
+}
+
+/// Rules for an element.
+struct ElementRule {
+    name: &'static str,
+    attributes: &'static [AttrRule],
+    /// Groups of known child elements.
+    children: &'static [&'static [ChildRule]],
+    /// Whether other attributes and child elements are allowed.
+    open: bool,
+    /// Whether known child elements must be in the order of `children`.
+    sequence: bool,
+}
+
+impl ElementRule {
+    fn children(&self) -> impl Iterator<Item = &'static ChildRule> {
+        self.children.iter().flat_map(|group| group.iter())
+    }
+}
+
+struct ChildRule {
+    rule: &'static ElementRule,
+    min: usize,
+    /// Maximum number of occurrences, or `None` if unbounded.
+    max: Option<usize>,
+}
+
+struct AttrRule {
+    name: &'static str,
+    usage: Use,
+    kind: Kind,
+}
+
+enum Use {
+    Optional,
+    Required,
+    /// Required unless one of the given attributes is present.
+    RequiredUnless(&'static [&'static str]),
+}
+
+/// Attribute value types.
+#[cfg_attr(not(feature = "tak"), allow(dead_code))]
+enum Kind {
+    Text,
+    /// A decimal number in the given (inclusive) range.
+    Decimal(f64, f64),
+    Integer,
+    Boolean,
+    DateTime,
+    /// A CoT type, e.g. `a-f-G-U-C`, optionally followed by `;` and anything.
+    CotType,
+    /// A `how` code, e.g. `h-g-i-g-o`.
+    How,
+}
+
+impl Kind {
+    /// Message describing why `value` is invalid, if it is.
+    fn check(&self, value: &str) -> Option<String> {
+        let is_code = |s: &str| {
+            s.split('-').all(|part| {
+                !part.is_empty() && part.chars().all(|c| c.is_alphanumeric() || c == '_')
+            })
+        };
+        match self {
+            Kind::Text => None,
+            Kind::Decimal(min, max) => match value.trim() {
+                v if !is_decimal(v) => Some("must be a decimal number".into()),
+                v => match v.parse::<f64>() {
+                    Ok(v) if (*min..=*max).contains(&v) => None,
+                    _ if *max == f64::INFINITY => Some(format!("must be at least {}", min)),
+                    _ => Some(format!("must be between {} and {}", min, max)),
+                },
+            },
+            Kind::Integer => value
+                .trim()
+                .parse::<i64>()
+                .err()
+                .map(|_| "must be an integer".into()),
+            Kind::Boolean => (!matches!(value.trim(), "true" | "false" | "1" | "0"))
+                .then(|| "must be true or false".into()),
+            Kind::DateTime => (!is_date_time(value.trim()))
+                .then(|| "must be an xs:dateTime, e.g. 2023-10-24T03:16:14Z".into()),
+            Kind::CotType => (!is_code(value.split(';').next().unwrap_or_default()))
+                .then(|| "must be a CoT type, e.g. a-f-G".into()),
+            Kind::How => (!is_code(value)).then(|| "must be a how code, e.g. m-g".into()),
+        }
+    }
+}
+
+/// Whether `value` is in the lexical space of XML Schema's `xs:decimal`:
+/// `[+-]?(\d+(\.\d*)?|\.\d+)`, so without exponents, `INF` or `NaN`.
+fn is_decimal(value: &str) -> bool {
+    let value = value.strip_prefix(['+', '-']).unwrap_or(value);
+    let (int, fraction) = value.split_once('.').unwrap_or((value, ""));
+    (!int.is_empty() || !fraction.is_empty())
+        && int
+            .bytes()
+            .chain(fraction.bytes())
+            .all(|b| b.is_ascii_digit())
+}
+
+/// Whether `value` is in the lexical space of XML Schema's `xs:dateTime`:
+/// `-?YYYY-MM-DDThh:mm:ss(.s+)?(Z|(+|-)hh:mm)?`, with a year of at least 4 digits and hour 24 only
+/// for `24:00:00`.
+fn is_date_time(value: &str) -> bool {
+    let digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    let number = |s: &str, len: usize| {
+        (s.len() == len && digits(s))
+            .then(|| s.parse::<u32>().ok())
+            .flatten()
+    };
+    let Some((date, time)) = value.split_once('T') else {
+        return false;
+    };
+
+    let date = date.strip_prefix('-').unwrap_or(date);
+    let mut fields = date.rsplitn(3, '-');
+    let (Some(day), Some(month), Some(year)) = (fields.next(), fields.next(), fields.next()) else {
+        return false;
+    };
+    if year.len() < 4 || (year.len() > 4 && year.starts_with('0')) || !digits(year) {
+        return false;
+    }
+    let (Some(month), Some(day)) = (number(month, 2), number(day, 2)) else {
+        return false;
+    };
+    // Leap years repeat every 400 years, so the last 4 digits of the year decide the month length.
+    let leap_year = year[year.len() - 4..].parse::<i32>().unwrap_or_default() + 2000;
+    if year == "0000" || NaiveDate::from_ymd_opt(leap_year, month, day).is_none() {
+        return false;
+    }
+
+    let (time, zone) = match time.find(['Z', '+', '-']) {
+        Some(i) => time.split_at(i),
+        None => (time, ""),
+    };
+    let valid_zone = match zone.as_bytes() {
+        [] | [b'Z'] => true,
+        [b'+' | b'-', ..] => match zone[1..].split_once(':') {
+            Some((hours, minutes)) => match (number(hours, 2), number(minutes, 2)) {
+                (Some(14), Some(0)) => true,
+                (Some(hours), Some(minutes)) => hours < 14 && minutes < 60,
+                _ => false,
+            },
+            None => false,
+        },
+        _ => false,
+    };
+    let (seconds, fraction) = time.split_once('.').unwrap_or((time, "0"));
+    let mut fields = seconds.split(':');
+    let (Some(hours), Some(minutes), Some(seconds), None) =
+        (fields.next(), fields.next(), fields.next(), fields.next())
+    else {
+        return false;
+    };
+    let valid_time = match (number(hours, 2), number(minutes, 2), number(seconds, 2)) {
+        (Some(24), Some(0), Some(0)) => fraction.bytes().all(|b| b == b'0'),
+        (Some(hours), Some(minutes), Some(seconds)) => hours < 24 && minutes < 60 && seconds < 60,
+        _ => false,
+    };
+    valid_zone && valid_time && digits(fraction)
+}
+
+const fn attr(name: &'static str, usage: Use, kind: Kind) -> AttrRule {
+    AttrRule { name, usage, kind }
+}
+
+const fn child(rule: &'static ElementRule, min: usize, max: Option<usize>) -> ChildRule {
+    ChildRule { rule, min, max }
+}
+
+const ANY: f64 = f64::INFINITY;
+
+static EVENT: ElementRule = ElementRule {
+    name: "event",
+    attributes: &[
+        attr("version", Use::Required, Kind::Decimal(2.0, ANY)),
+        attr("uid", Use::Required, Kind::Text),
+        attr("type", Use::Required, Kind::CotType),
+        attr("time", Use::Required, Kind::DateTime),
+        attr("start", Use::Required, Kind::DateTime),
+        attr("stale", Use::Required, Kind::DateTime),
+        attr("how", Use::Required, Kind::How),
+        attr("opex", Use::Optional, Kind::Text),
+        attr("qos", Use::Optional, Kind::Text),
+        attr("access", Use::Optional, Kind::Text),
+    ],
+    children: &[&[child(&POINT, 1, Some(1)), child(&DETAIL, 0, Some(1))]],
+    open: false,
+    sequence: true,
+};
+
+static POINT: ElementRule = ElementRule {
+    name: "point",
+    attributes: &[
+        attr("lat", Use::Required, Kind::Decimal(-90.0, 90.0)),
+        attr("lon", Use::Required, Kind::Decimal(-180.0, 180.0)),
+        attr("hae", Use::Required, Kind::Decimal(-ANY, ANY)),
+        attr("ce", Use::Required, Kind::Decimal(0.0, ANY)),
+        attr("le", Use::Required, Kind::Decimal(0.0, ANY)),
+    ],
+    children: &[],
+    open: false,
+    sequence: false,
+};
+
+static DETAIL: ElementRule = ElementRule {
+    name: "detail",
+    attributes: &[],
+    children: &[&COT_DETAIL, &TAK_DETAIL],
+    open: true,
+    sequence: false,
+};
+
+/// Detail elements with a MITRE sub-schema.
+static COT_DETAIL: [ChildRule; 5] = [
+    child(&CONTACT, 0, Some(1)),
+    child(&LINK, 0, None),
+    child(&REMARKS, 0, Some(1)),
+    child(&TRACK, 0, Some(1)),
+    child(&UID, 0, Some(1)),
+];
+
+/// Detail sub-schema element with attributes, and no rules for its content.
+const fn detail_element(name: &'static str, attributes: &'static [AttrRule]) -> ElementRule {
+    ElementRule {
+        name,
+        attributes,
+        children: &[],
+        open: true,
+        sequence: false,
+    }
+}
+
+static CONTACT: ElementRule = detail_element(
+    "contact",
+    &[
+        attr("callsign", Use::Required, Kind::Text),
+        attr("endpoint", Use::Optional, Kind::Text),
+        attr("phone", Use::Optional, Kind::Text),
+    ],
+);
+
+/// Links to other events. TAK shape vertices and lines are links with only a `point` or `line`.
+static LINK: ElementRule = detail_element(
+    "link",
+    &[
+        attr("uid", Use::RequiredUnless(&["point", "line"]), Kind::Text),
+        attr(
+            "type",
+            Use::RequiredUnless(&["point", "line"]),
+            Kind::CotType,
+        ),
+        attr(
+            "relation",
+            Use::RequiredUnless(&["point", "line"]),
+            Kind::Text,
+        ),
+        attr("production_time", Use::Optional, Kind::DateTime),
+    ],
+);
+
+static REMARKS: ElementRule = detail_element(
+    "remarks",
+    &[
+        attr("source", Use::Optional, Kind::Text),
+        attr("to", Use::Optional, Kind::Text),
+        attr("time", Use::Optional, Kind::DateTime),
+    ],
+);
+
+static TRACK: ElementRule = detail_element(
+    "track",
+    &[
+        attr("course", Use::Required, Kind::Decimal(0.0, 360.0)),
+        attr("speed", Use::Required, Kind::Decimal(0.0, ANY)),
+    ],
+);
+
+static UID: ElementRule = detail_element("uid", &[attr("Droid", Use::Optional, Kind::Text)]);
+
+/// Detail elements with an ATAK marker schema.
+#[cfg(not(feature = "tak"))]
+static TAK_DETAIL: [ChildRule; 0] = [];
+
+/// Detail elements with an ATAK marker schema.
+#[cfg(feature = "tak")]
+static TAK_DETAIL: [ChildRule; 10] = [
+    child(&GROUP, 0, Some(1)),
+    child(&TAKV, 0, Some(1)),
+    child(&STATUS, 0, Some(1)),
+    child(&PRECISION_LOCATION, 0, Some(1)),
+    child(&USERICON, 0, Some(1)),
+    child(&COLOR, 0, Some(1)),
+    child(&STROKE_COLOR, 0, Some(1)),
+    child(&STROKE_WEIGHT, 0, Some(1)),
+    child(&FILL_COLOR, 0, Some(1)),
+    child(&LABELS_ON, 0, Some(1)),
+];
+
+#[cfg(feature = "tak")]
+static GROUP: ElementRule = detail_element(
+    "__group",
+    &[
+        attr("name", Use::Required, Kind::Text),
+        attr("role", Use::Required, Kind::Text),
+    ],
+);
+
+#[cfg(feature = "tak")]
+static TAKV: ElementRule = detail_element(
+    "takv",
+    &[
+        attr("device", Use::Optional, Kind::Text),
+        attr("platform", Use::Optional, Kind::Text),
+        attr("os", Use::Optional, Kind::Text),
+        attr("version", Use::Optional, Kind::Text),
+    ],
+);
+
+#[cfg(feature = "tak")]
+static STATUS: ElementRule = detail_element(
+    "status",
+    &[
+        attr("battery", Use::Optional, Kind::Decimal(0.0, 100.0)),
+        attr("readiness", Use::Optional, Kind::Boolean),
+    ],
+);
+
+#[cfg(feature = "tak")]
+static PRECISION_LOCATION: ElementRule = detail_element(
+    "precisionlocation",
+    &[
+        attr("geopointsrc", Use::Optional, Kind::Text),
+        attr("altsrc", Use::Optional, Kind::Text),
+    ],
+);
+
+#[cfg(feature = "tak")]
+static USERICON: ElementRule = detail_element(
+    "usericon",
+    &[attr("iconsetpath", Use::Required, Kind::Text)],
+);
+
+/// Marker color, as `argb` for markers and `value` for shapes.
+#[cfg(feature = "tak")]
+static COLOR: ElementRule = detail_element(
+    "color",
+    &[
+        attr("argb", Use::Optional, Kind::Integer),
+        attr("value", Use::Optional, Kind::Integer),
+    ],
+);
+
+#[cfg(feature = "tak")]
+static STROKE_COLOR: ElementRule = detail_element(
+    "strokeColor",
+    &[attr("value", Use::Required, Kind::Integer)],
+);
+
+#[cfg(feature = "tak")]
+static STROKE_WEIGHT: ElementRule = detail_element(
+    "strokeWeight",
+    &[attr("value", Use::Required, Kind::Decimal(0.0, ANY))],
+);
+
+#[cfg(feature = "tak")]
+static FILL_COLOR: ElementRule =
+    detail_element("fillColor", &[attr("value", Use::Required, Kind::Integer)]);
+
+#[cfg(feature = "tak")]
+static LABELS_ON: ElementRule =
+    detail_element("labels_on", &[attr("value", Use::Required, Kind::Boolean)]);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::base::{from_str, CotBase};
+    use crate::examples::{COT_BASE_EXAMPLE, COT_STRIKE_EXAMPLE, COT_TRACK_EXAMPLE};
+    use crate::writer::CotWriter;
+
+    #[test]
+    fn test_check_examples() {
+        // The MITRE example has no `how`, and has `<detail>` before `<point>`.
+        let violations = check(COT_BASE_EXAMPLE).unwrap();
+        assert_eq!(violations.len(), 2);
+        assert_eq!(violations[0].path, "event/@how");
+        assert_eq!(violations[0].position.line, 3);
+        assert_eq!(violations[1].path, "event/point");
+        assert_eq!(violations[1].position.line, 11);
+        assert_eq!(violations[1].message, "<point> must come before <detail>");
+
+        assert_eq!(check(COT_TRACK_EXAMPLE).unwrap(), vec![]);
+        // `<_tasking= ...>` is an unknown element, but the remarks time has a space in it.
+        let violations = check(COT_STRIKE_EXAMPLE).unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].path, "event/detail/remarks/@time");
+        assert_eq!(violations[0].position.line, 10);
+        assert_eq!(
+            violations[0].value.as_deref(),
+            Some("2023-10- 24T03:16:14.985238Z")
+        );
+
+        #[cfg(feature = "tak")]
+        for entry in std::fs::read_dir("src/tak/examples").unwrap() {
+            let path = entry.unwrap().path();
+            let input = std::fs::read_to_string(&path).unwrap();
+            assert_eq!(check(&input).unwrap(), vec![], "{}", path.display());
+        }
+    }
+
+    #[test]
+    fn test_check_violations() {
+        let input = r#"<event version="2.0" uid="u" type="a-f-G" how="m g" time="2023-10-24T03:16:14Z"
+ start="2023-10-24T03:16:14Z" stale="yesterday" color="red">
+ <point lat="1.0" lon="2.0" hae="0" ce="-1" le="0"/>
+ <detail>
+  <track course="361" speed="0"/>
+  <track course="0"/>
+  <link uid="x" type="a-f-G"/>
+  <link point="1.0,2.0"/>
+  <__extension anything="goes"/>
+ </detail>
+ <point lat="1.0" lon="2.0" hae="0" ce="0" le="0"/>
+ <remarks/>
+</event>"#;
+        let violations: Vec<_> = check(input)
+            .unwrap()
+            .into_iter()
+            .map(|v| (v.path, v.position.line, v.message))
+            .collect();
+        let expected = [
+            ("event/@how", 1, "must be a how code, e.g. m-g"),
+            (
+                "event/@stale",
+                2,
+                "must be an xs:dateTime, e.g. 2023-10-24T03:16:14Z",
+            ),
+            ("event/@color", 2, "unexpected attribute of <event>"),
+            ("event/point/@ce", 3, "must be at least 0"),
+            ("event/detail/track/@course", 5, "must be between 0 and 360"),
+            ("event/detail/track", 6, "more than 1 <track>"),
+            (
+                "event/detail/track/@speed",
+                6,
+                "missing required attribute of <track>",
+            ),
+            (
+                "event/detail/link/@relation",
+                7,
+                "missing required attribute of <link>",
+            ),
+            ("event/point", 11, "<point> must come before <detail>"),
+            ("event/point", 11, "more than 1 <point>"),
+            ("event/remarks", 12, "unexpected element in <event>"),
+        ];
+        let expected: Vec<_> = expected
+            .into_iter()
+            .map(|(path, line, message)| (path.to_string(), line, message.to_string()))
+            .collect();
+        assert_eq!(violations, expected);
+
+        let e: Error = check("<event/>").unwrap().remove(0).into();
+        assert_eq!(e.path(), Some("event/@version"));
+        assert_eq!(
+            e.to_string(),
+            "event/@version at line 1, column 1: message field error: missing required attribute of <event>"
+        );
+        let violations = check("<event version='2.0'/>").unwrap();
+        assert_eq!(
+            violations.last().unwrap().message,
+            "missing <point> in <event>"
+        );
+    }
+
+    #[test]
+    fn test_check_order() {
+        let cot: CotBase = from_str(COT_TRACK_EXAMPLE).unwrap();
+        let violations = check(&quick_xml::se::to_string(&cot).unwrap()).unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].path, "event/point");
+        assert_eq!(violations[0].message, "<point> must come before <detail>");
+        let xml = CotWriter::new().to_string(&cot).unwrap();
+        assert_eq!(check(&xml).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_decimal() {
+        for value in ["0", "-1.5", "+2", "3.", ".5", "007.250"] {
+            assert!(is_decimal(value), "{}", value);
+        }
+        for value in [
+            "", ".", "-", "+.", "inf", "infinity", "NaN", "1e3", "1.0E3", "1.2.3", "0x10", "1,5",
+        ] {
+            assert!(!is_decimal(value), "{}", value);
+        }
+        assert_eq!(
+            Kind::Decimal(0.0, ANY).check("1e3").as_deref(),
+            Some("must be a decimal number")
+        );
+        assert_eq!(Kind::Decimal(0.0, ANY).check(" 1000 "), None);
+    }
+
+    #[test]
+    fn test_date_time() {
+        for value in [
+            "2023-10-24T03:16:14Z",
+            "2023-10-24T03:16:14.985238Z",
+            "2023-10-24T05:16:14.5+02:00",
+            "2023-10-23T22:16:14-05:00",
+            "2023-10-24T03:16:14",
+            "2024-02-29T00:00:00Z",
+            "2023-10-24T24:00:00Z",
+            "2023-10-24T03:16:14+14:00",
+            "-0044-03-15T12:00:00",
+            "12023-10-24T03:16:14Z",
+        ] {
+            assert!(is_date_time(value), "{}", value);
+        }
+        for value in [
+            "2023-10-24t03:16:14Z",
+            "2023-10-24T03:16:14z",
+            "2023-10-24 03:16:14Z",
+            "2023-10- 24T03:16:14Z",
+            "2023-10-24T03:16:14+0200",
+            "2023-10-24T03:16:14+02",
+            "2023-10-24T03:16:14+14:30",
+            "2023-10-24T03:16:14.Z",
+            "2023-02-29T00:00:00Z",
+            "2023-10-24T24:00:01Z",
+            "2023-10-24T03:16:60Z",
+            "2023-10-24T3:16:14Z",
+            "023-10-24T03:16:14Z",
+            "02023-10-24T03:16:14Z",
+            "0000-01-01T00:00:00Z",
+            "2023-10-24",
+        ] {
+            assert!(!is_date_time(value), "{}", value);
+        }
+    }
+}
@@ -0,0 +1,206 @@
+//! Tracking of GeoChat delivery and read receipts for sent messages.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::base::Cot;
+
+use super::detail::{ChatReceiptKind, TakChatDetail, TakChatReceiptDetail};
+
+/// Receipt state of a sent message for a single recipient.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecipientStatus {
+    /// Most recent receipt, or `None` if no receipt has arrived yet.
+    pub receipt: Option<ChatReceiptKind>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub read_at: Option<DateTime<Utc>>,
+    /// Set once the message was not read before the tracker's timeout.
+    pub timed_out: bool,
+}
+
+/// A change in receipt state reported by [`ReceiptTracker`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReceiptUpdate {
+    pub message_id: String,
+    pub recipient_uid: String,
+    pub kind: ChatReceiptKind,
+}
+
+#[derive(Clone, Debug)]
+struct TrackedMessage {
+    sent_at: DateTime<Utc>,
+    recipients: HashMap<String, RecipientStatus>,
+}
+
+/// Correlates outgoing GeoChat messages with incoming delivery and read receipts.
+///
+/// Messages are identified by their `messageId`. Recipients which have not read a message within
+/// `timeout` of it being sent are reported once by [`ReceiptTracker::check_timeouts()`].
+#[derive(Clone, Debug)]
+pub struct ReceiptTracker {
+    timeout: Duration,
+    messages: HashMap<String, TrackedMessage>,
+}
+
+impl ReceiptTracker {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            messages: HashMap::new(),
+        }
+    }
+
+    /// Start tracking a sent message, expecting receipts from `recipients` (by uid). For a direct
+    /// message this is the recipient's uid, `message.detail.chat.id`.
+    ///
+    /// Returns the message id, or `None` if the message has none.
+    pub fn sent<I>(&mut self, message: &Cot<TakChatDetail>, recipients: I) -> Option<String>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let message_id = message.detail.chat.message_id.clone()?;
+        let recipients = recipients
+            .into_iter()
+            .map(|uid| {
+                let status = RecipientStatus {
+                    receipt: None,
+                    delivered_at: None,
+                    read_at: None,
+                    timed_out: false,
+                };
+                (uid, status)
+            })
+            .collect();
+        let tracked = TrackedMessage {
+            sent_at: *message.time,
+            recipients,
+        };
+        self.messages.insert(message_id.clone(), tracked);
+        Some(message_id)
+    }
+
+    /// Process an incoming receipt. Returns the update if it is for a tracked message and
+    /// recipient and advances its state. Duplicate receipts, and a delivery receipt after a read
+    /// receipt, are ignored and don't change the recorded receipt times.
+    pub fn receipt(&mut self, receipt: &Cot<TakChatReceiptDetail>) -> Option<ReceiptUpdate> {
+        let kind = ChatReceiptKind::from_cot_type(&receipt.cot_type)?;
+        let message_id = receipt.detail.message_id().unwrap_or(&receipt.uid);
+        let recipient_uid = receipt.detail.receiver_uid();
+        let status = self
+            .messages
+            .get_mut(message_id)?
+            .recipients
+            .get_mut(recipient_uid)?;
+        if status.receipt.is_some_and(|r| r >= kind) {
+            return None;
+        }
+        let received_at = match kind {
+            ChatReceiptKind::Delivered => &mut status.delivered_at,
+            ChatReceiptKind::Read => &mut status.read_at,
+        };
+        received_at.get_or_insert(*receipt.time);
+        status.receipt = Some(kind);
+        Some(ReceiptUpdate {
+            message_id: message_id.to_string(),
+            recipient_uid: recipient_uid.to_string(),
+            kind,
+        })
+    }
+
+    /// Per-recipient status of a tracked message.
+    pub fn status(&self, message_id: &str) -> Option<&HashMap<String, RecipientStatus>> {
+        self.messages.get(message_id).map(|m| &m.recipients)
+    }
+
+    /// Whether all recipients have read the message.
+    pub fn is_read(&self, message_id: &str) -> bool {
+        self.status(message_id).is_some_and(|recipients| {
+            recipients
+                .values()
+                .all(|s| s.receipt == Some(ChatReceiptKind::Read))
+        })
+    }
+
+    /// Mark recipients which have not read their message within the timeout as timed out, and
+    /// return them as `(message id, recipient uid)` pairs. Each recipient is only reported once.
+    pub fn check_timeouts(&mut self, now: DateTime<Utc>) -> Vec<(String, String)> {
+        let mut timed_out = vec![];
+        for (message_id, message) in self.messages.iter_mut() {
+            if now - message.sent_at < self.timeout {
+                continue;
+            }
+            for (uid, status) in message.recipients.iter_mut() {
+                if status.receipt != Some(ChatReceiptKind::Read) && !status.timed_out {
+                    status.timed_out = true;
+                    timed_out.push((message_id.clone(), uid.clone()));
+                }
+            }
+        }
+        timed_out
+    }
+
+    /// Stop tracking a message.
+    pub fn forget(&mut self, message_id: &str) {
+        self.messages.remove(message_id);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::tak::create::{chat_message, chat_receipt, direct_message};
+    use crate::tak::detail::ChatParticipant;
+
+    use super::*;
+
+    #[test]
+    fn test_receipt_tracker() {
+        let cp = ChatParticipant::new("cp-1", "Command Post");
+        let op1 = ChatParticipant::new("ANDROID-1", "HOPE");
+        let op2 = ChatParticipant::new("ANDROID-2", "FAITH");
+        let mut tracker = ReceiptTracker::new(Duration::minutes(5));
+
+        let msg = chat_message(&cp, "Tasking", "Move to CP2");
+        let id = tracker
+            .sent(&msg, [op1.uid.clone(), op2.uid.clone()])
+            .unwrap();
+
+        let delivered = chat_receipt(&msg, &op1, ChatReceiptKind::Delivered);
+        let update = tracker.receipt(&delivered).unwrap();
+        assert_eq!(update.recipient_uid, op1.uid);
+        assert_eq!(update.kind, ChatReceiptKind::Delivered);
+        let read = chat_receipt(&msg, &op1, ChatReceiptKind::Read);
+        assert!(tracker.receipt(&read).is_some());
+        // Late delivery receipt doesn't downgrade state
+        assert!(tracker.receipt(&delivered).is_none());
+        assert!(!tracker.is_read(&id));
+        // Duplicate receipts keep the first receipt time
+        let mut duplicate = read.clone();
+        duplicate.time = duplicate.time + Duration::minutes(1);
+        assert!(tracker.receipt(&duplicate).is_none());
+        let status = &tracker.status(&id).unwrap()[&op1.uid];
+        assert_eq!(status.delivered_at, Some(*delivered.time));
+        assert_eq!(status.read_at, Some(*read.time));
+
+        // Receipts from unknown recipients or for unknown messages are ignored
+        let other = direct_message(&cp, &op1, "hi");
+        assert!(tracker
+            .receipt(&chat_receipt(&other, &op1, ChatReceiptKind::Read))
+            .is_none());
+
+        assert!(tracker.check_timeouts(*msg.time).is_empty());
+        let later = *msg.time + Duration::minutes(6);
+        assert_eq!(
+            tracker.check_timeouts(later),
+            [(id.clone(), op2.uid.clone())]
+        );
+        assert!(tracker.check_timeouts(later).is_empty());
+        let status = tracker.status(&id).unwrap();
+        assert_eq!(status[&op1.uid].receipt, Some(ChatReceiptKind::Read));
+        assert!(status[&op2.uid].timed_out);
+
+        let read = chat_receipt(&msg, &op2, ChatReceiptKind::Read);
+        assert!(tracker.receipt(&read).is_some());
+        assert!(tracker.is_read(&id));
+    }
+}
@@ -1,44 +1,802 @@
-use chrono::Utc;
+//! Support for creating TAK CoT messages with reasonable defaults for quickly getting integration
+//! working.
+//!
+//! Instead of providing builder APIs, we implement [`Default`] on different CoT variants: You'll
+//! want to modify key fields like `point` with your real coordinates. For example:
+//! ```rust
+//! use cot_proto::base::{Cot, Point};
+//! use cot_proto::tak::detail::TakMarkerDetail;
+//! let mut cot = Cot::<TakMarkerDetail>::default();
+//! cot.point.lat = 10.0;
+//! cot.point.lon = 90.0;
+//! let xml_text = quick_xml::se::to_string(&cot).unwrap();
+//! ```
 
-use crate::base::{Cot, Point};
+use chrono::{Duration, Utc};
 
-use super::detail::TakMarkerDetail;
+use crate::base::{Cot, Point};
+use crate::time::Timestamp;
+use crate::Error;
 
-/// Support for creating TAK CoT messages with reasonable defaults for quickly getting integration
-/// working.
-///
-/// Instead of providing builder APIs, we implement [`Default`] on different CoT variants: You'll
-/// want to modify key fields like `point` with your real coordinates. For example:
-/// ```rust
-/// use cot_proto::base::{Cot, Point};
-/// use cot_proto::tak::detail::TakMarkerDetail;
-/// let mut cot = Cot::<TakMarkerDetail>::default();
-/// cot.point.lat = 10.0;
-/// cot.point.lon = 90.0;
-/// let xml_text = quick_xml::se::to_string(&cot).unwrap();
-/// ```
+use super::detail::{
+    rectangle_corners, BearingUnits, Bullseye, BullseyeDetail, Chat, ChatGroup, ChatLink,
+    ChatParticipant, ChatReceiptKind, ChatRemarks, Contact, Ellipse, Emergency, EmergencyDetail,
+    EmergencyLink, EmergencyType, Empty, KmlStyle, LinkPoint, Medevac, MedevacDetail, NorthRef,
+    PrecisionLocation, RangeBearingDetail, RangeUnits, RouteLink, RoutePointType, Shape, StyleLink,
+    TakChatDetail, TakChatReceiptDetail, TakColor, TakMarkerDetail, TakPolygonDetail,
+    TakRectangleDetail, TakRouteDetail, TakSaDetail, TakShapeDetail, TakTelestrationDetail, Takv,
+    TelestrationLink, Tog, ValueElement, VertexLink,
+};
 
 /// Default CoT type for marker messages.
 pub const DEFAULT_COT_TYPE_MARKER: &str = "a-o-G";
 
+/// Default CoT type for self SA messages: friendly ground unit, combat.
+pub const DEFAULT_COT_TYPE_SA: &str = "a-f-G-U-C";
+
+/// CoT type for route messages.
+pub const COT_TYPE_ROUTE: &str = "b-m-r";
+
+/// CoT type for drawn circle and ellipse shapes.
+pub const COT_TYPE_SHAPE_CIRCLE: &str = "u-d-c-c";
+
+/// CoT type for freehand polygons and polylines.
+pub const COT_TYPE_SHAPE_FREE: &str = "u-d-f";
+
+/// CoT type for rectangles.
+pub const COT_TYPE_SHAPE_RECTANGLE: &str = "u-d-r";
+
+/// CoT type for telestrations (multi-stroke freehand drawings).
+pub const COT_TYPE_SHAPE_TELESTRATION: &str = "u-d-f-m";
+
+/// CoT type for range & bearing lines.
+pub const COT_TYPE_RANGE_BEARING: &str = "u-rb-a";
+
+/// CoT type for bullseyes.
+pub const COT_TYPE_BULLSEYE: &str = "u-r-b-bullseye";
+
+/// CoT type for GeoChat messages.
+pub const COT_TYPE_CHAT: &str = "b-t-f";
+
+/// CoT type for MEDEVAC / CASEVAC requests.
+pub const COT_TYPE_MEDEVAC: &str = "b-r-f-h-c";
+
+/// Default shape stroke color: opaque white.
+pub const DEFAULT_STROKE_COLOR: TakColor = TakColor::WHITE;
+
+/// Default shape fill color: translucent white.
+pub const DEFAULT_FILL_COLOR: TakColor = TakColor::WHITE.with_alpha(0x96);
+
+/// Create a CoT message with a new uid, timestamps starting now, and the given stale time.
+fn new_cot<D>(cot_type: &str, how: &str, stale: Duration, detail: D, point: Point) -> Cot<D> {
+    let now = Timestamp::now();
+    Cot {
+        version: "2.0".to_string(),
+        uid: uuid::Uuid::new_v4().to_string(),
+        cot_type: cot_type.to_string(),
+        time: now.clone(),
+        start: now.clone(),
+        stale: now + stale,
+        how: Some(how.to_string()),
+        detail,
+        point,
+    }
+}
+
 /// TAK CoT Marker
 impl Default for Cot<TakMarkerDetail> {
     fn default() -> Self {
-        let now = Utc::now();
         let detail = TakMarkerDetail {
             ..Default::default()
         };
-        Self {
-            version: "2.0".to_string(),
-            uid: uuid::Uuid::new_v4().to_string(),
-            cot_type: DEFAULT_COT_TYPE_MARKER.to_string(),
-            time: now,
-            start: now,
-            // now plus 1 day
-            stale: now + chrono::Duration::days(1),
-            how: Some("m-g".to_string()),
+        new_cot(
+            DEFAULT_COT_TYPE_MARKER,
+            "m-g",
+            Duration::days(1),
+            detail,
+            Point::north_pole(),
+        )
+    }
+}
+
+/// TAK self SA (position report), identifying this software in `takv`.
+impl Default for Cot<TakSaDetail> {
+    fn default() -> Self {
+        let detail = TakSaDetail {
+            takv: Some(Takv {
+                platform: Some("cot-proto".to_string()),
+                version: Some(env!("CARGO_PKG_VERSION").to_string()),
+                ..Default::default()
+            }),
+            contact: Contact {
+                endpoint: Some("*:-1:stcp".to_string()),
+                ..Default::default()
+            },
+            group: Some(Default::default()),
+            status: Some(Default::default()),
+            ..Default::default()
+        };
+        new_cot(
+            DEFAULT_COT_TYPE_SA,
+            "m-g",
+            Duration::minutes(5),
+            detail,
+            Point::north_pole(),
+        )
+    }
+}
+
+/// TAK CoT Route, with no points.
+impl Default for Cot<TakRouteDetail> {
+    fn default() -> Self {
+        let detail = TakRouteDetail {
+            stroke_color: Some(ValueElement::from(TakColor::WHITE)),
+            stroke_weight: Some(ValueElement::from(3.0)),
+            archive: Some(Empty {}),
+            labels_on: Some(ValueElement::from(false)),
+            color: Some(ValueElement::from(TakColor::WHITE)),
+            ..Default::default()
+        };
+        new_cot(
+            COT_TYPE_ROUTE,
+            "h-e",
+            Duration::days(1),
             detail,
-            point: Point::north_pole(),
+            Point::from_lat_lon(0.0, 0.0),
+        )
+    }
+}
+
+/// Create a route through `waypoints`, in order.
+///
+/// The first waypoint is the start point, named "`<callsign>` SP", and the rest are numbered
+/// checkpoints using the route prefix, e.g. "CP1".
+pub fn route(callsign: &str, waypoints: &[Point]) -> Cot<TakRouteDetail> {
+    let mut cot = Cot::<TakRouteDetail>::default();
+    cot.detail.contact = Contact {
+        callsign: callsign.to_string(),
+        ..Default::default()
+    };
+    let prefix = cot.detail.link_attr.prefix.clone();
+    cot.detail.links = waypoints
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            let name = match i {
+                0 => format!("{} SP", callsign),
+                _ => format!("{}{}", prefix, i),
+            };
+            RouteLink::new(RoutePointType::Waypoint, &name, LinkPoint::from(p))
+        })
+        .collect();
+    if let Some(first) = waypoints.first() {
+        cot.point = first.clone();
+    }
+    cot
+}
+
+/// TAK CoT circle shape, with a 100m radius.
+impl Default for Cot<TakShapeDetail> {
+    fn default() -> Self {
+        let stroke_weight = 4.0;
+        let detail = TakShapeDetail {
+            shape: Shape {
+                ellipse: Ellipse::circle(100.0),
+                link: None,
+            },
+            stroke_color: Some(ValueElement::from(DEFAULT_STROKE_COLOR)),
+            stroke_weight: Some(ValueElement::from(stroke_weight)),
+            fill_color: Some(ValueElement::from(DEFAULT_FILL_COLOR)),
+            archive: Some(Empty {}),
+            labels_on: Some(ValueElement::from(true)),
+            precisionlocation: Some(PrecisionLocation::default()),
+            ..Default::default()
+        };
+        let mut cot = new_cot(
+            COT_TYPE_SHAPE_CIRCLE,
+            "h-e",
+            Duration::days(1),
+            detail,
+            Point::north_pole(),
+        );
+        let style = KmlStyle::new(DEFAULT_STROKE_COLOR, stroke_weight, DEFAULT_FILL_COLOR);
+        cot.detail.shape.link = Some(StyleLink::new(&cot.uid, style));
+        cot
+    }
+}
+
+/// Create an ellipse shape centered on `center`.
+pub fn ellipse(callsign: &str, center: Point, ellipse: Ellipse) -> Cot<TakShapeDetail> {
+    let mut cot = Cot::<TakShapeDetail>::default();
+    cot.detail.contact.callsign = callsign.to_string();
+    cot.detail.shape.ellipse = ellipse;
+    cot.point = center;
+    cot
+}
+
+/// Create a circle shape with `radius` in meters, e.g. a range ring around an asset.
+pub fn circle(callsign: &str, center: Point, radius: f64) -> Cot<TakShapeDetail> {
+    ellipse(callsign, center, Ellipse::circle(radius))
+}
+
+/// TAK CoT freehand polygon, with no vertices.
+impl Default for Cot<TakPolygonDetail> {
+    fn default() -> Self {
+        let detail = TakPolygonDetail {
+            stroke_color: Some(ValueElement::from(DEFAULT_STROKE_COLOR)),
+            stroke_weight: Some(ValueElement::from(4.0)),
+            fill_color: Some(ValueElement::from(DEFAULT_FILL_COLOR)),
+            archive: Some(Empty {}),
+            labels_on: Some(ValueElement::from(false)),
+            color: Some(ValueElement::from(DEFAULT_STROKE_COLOR)),
+            precisionlocation: Some(PrecisionLocation::default()),
+            ..Default::default()
+        };
+        new_cot(
+            COT_TYPE_SHAPE_FREE,
+            "h-e",
+            Duration::days(1),
+            detail,
+            Point::north_pole(),
+        )
+    }
+}
+
+/// Create a filled, closed polygon with the given vertices, e.g. a no-go area.
+///
+/// The first vertex is repeated at the end if needed to close the polygon.
+pub fn polygon(callsign: &str, vertices: &[Point]) -> Cot<TakPolygonDetail> {
+    let mut cot = polyline(callsign, vertices, true);
+    cot.detail.fill_color = Some(ValueElement::from(DEFAULT_FILL_COLOR));
+    cot
+}
+
+/// Create an unfilled polyline with the given vertices. If `closed`, the first vertex is repeated
+/// at the end if needed.
+pub fn polyline(callsign: &str, vertices: &[Point], closed: bool) -> Cot<TakPolygonDetail> {
+    let mut cot = Cot::<TakPolygonDetail>::default();
+    cot.detail.contact.callsign = callsign.to_string();
+    cot.detail.fill_color = None;
+    cot.detail.links = vertices
+        .iter()
+        .map(|p| VertexLink::from(LinkPoint::from(p)))
+        .collect();
+    if closed && !cot.detail.is_closed() {
+        if let Some(first) = cot.detail.links.first().cloned() {
+            cot.detail.links.push(first);
         }
     }
+    if !vertices.is_empty() {
+        cot.point = centroid(vertices);
+    }
+    cot
+}
+
+/// TAK CoT rectangle, with no corners.
+impl Default for Cot<TakRectangleDetail> {
+    fn default() -> Self {
+        let detail = TakRectangleDetail {
+            stroke_color: Some(ValueElement::from(DEFAULT_STROKE_COLOR)),
+            stroke_weight: Some(ValueElement::from(3.0)),
+            fill_color: Some(ValueElement::from(DEFAULT_FILL_COLOR)),
+            tog: Some(Tog::default()),
+            archive: Some(Empty {}),
+            labels_on: Some(ValueElement::from(false)),
+            precisionlocation: Some(PrecisionLocation::default()),
+            ..Default::default()
+        };
+        new_cot(
+            COT_TYPE_SHAPE_RECTANGLE,
+            "h-e",
+            Duration::days(1),
+            detail,
+            Point::north_pole(),
+        )
+    }
+}
+
+/// Create a north-aligned rectangle spanned by two opposite corners.
+pub fn rectangle(callsign: &str, corner: &Point, opposite: &Point) -> Cot<TakRectangleDetail> {
+    let mut cot = Cot::<TakRectangleDetail>::default();
+    cot.detail.contact.callsign = callsign.to_string();
+    cot.detail.links = rectangle_corners(corner, opposite)
+        .into_iter()
+        .map(VertexLink::from)
+        .collect();
+    if let Some(center) = cot.detail.center() {
+        cot.point = center.to_point();
+    }
+    cot
+}
+
+/// TAK CoT telestration, with no strokes.
+impl Default for Cot<TakTelestrationDetail> {
+    fn default() -> Self {
+        let detail = TakTelestrationDetail {
+            stroke_color: Some(ValueElement::from(DEFAULT_STROKE_COLOR)),
+            stroke_weight: Some(ValueElement::from(4.0)),
+            archive: Some(Empty {}),
+            labels_on: Some(ValueElement::from(false)),
+            color: Some(ValueElement::from(DEFAULT_STROKE_COLOR)),
+            ..Default::default()
+        };
+        new_cot(
+            COT_TYPE_SHAPE_TELESTRATION,
+            "h-e",
+            Duration::days(1),
+            detail,
+            Point::north_pole(),
+        )
+    }
+}
+
+/// Create a telestration from freehand strokes, e.g. created with [`polyline()`].
+///
+/// The telestration's point is set to the center of the strokes' points.
+pub fn telestration(
+    callsign: &str,
+    strokes: &[Cot<TakPolygonDetail>],
+) -> Result<Cot<TakTelestrationDetail>, Error> {
+    let mut cot = Cot::<TakTelestrationDetail>::default();
+    cot.detail.contact.callsign = callsign.to_string();
+    cot.detail.links = strokes
+        .iter()
+        .map(TelestrationLink::from_event)
+        .collect::<Result<_, _>>()?;
+    let points: Vec<Point> = strokes.iter().map(|s| s.point.clone()).collect();
+    if !points.is_empty() {
+        cot.point = centroid(&points);
+    }
+    Ok(cot)
+}
+
+/// TAK CoT range & bearing line, with zero range.
+impl Default for Cot<RangeBearingDetail> {
+    fn default() -> Self {
+        let detail = RangeBearingDetail {
+            stroke_color: Some(ValueElement::from(TakColor::RED)),
+            stroke_weight: Some(ValueElement::from(3.0)),
+            archive: Some(Empty {}),
+            labels_on: Some(ValueElement::from(false)),
+            color: Some(ValueElement::from(TakColor::RED)),
+            ..RangeBearingDetail::new(
+                0.0,
+                RangeUnits::Meters,
+                0.0,
+                BearingUnits::Degrees,
+                NorthRef::True,
+            )
+        };
+        new_cot(
+            COT_TYPE_RANGE_BEARING,
+            "h-e",
+            Duration::days(1),
+            detail,
+            Point::north_pole(),
+        )
+    }
+}
+
+/// Create a range & bearing line from `start`, with range in meters and bearing in degrees from
+/// true north.
+///
+/// To express the line in other units, e.g. as reported by an observer in mils magnetic, set the
+/// detail's value and unit fields, or replace it using [`RangeBearingDetail::new()`].
+pub fn range_bearing(
+    callsign: &str,
+    start: Point,
+    range: f64,
+    bearing: f64,
+) -> Cot<RangeBearingDetail> {
+    let mut cot = Cot::<RangeBearingDetail>::default();
+    cot.detail.contact.callsign = callsign.to_string();
+    cot.detail.range = range.into();
+    cot.detail.bearing = bearing.into();
+    cot.point = start;
+    cot
+}
+
+/// TAK CoT bullseye, with magnetic bearings in degrees and ranges in meters.
+impl Default for Cot<BullseyeDetail> {
+    fn default() -> Self {
+        let detail = BullseyeDetail {
+            archive: Some(Empty {}),
+            precisionlocation: Some(PrecisionLocation::default()),
+            ..Default::default()
+        };
+        let mut cot = new_cot(
+            COT_TYPE_BULLSEYE,
+            "h-e",
+            Duration::days(1),
+            detail,
+            Point::north_pole(),
+        );
+        cot.detail.bullseye.bullseye_uid = cot.uid.clone();
+        cot
+    }
+}
+
+/// Create a bullseye named `title` at `center`.
+pub fn bullseye(title: &str, center: Point) -> Cot<BullseyeDetail> {
+    let mut cot = Cot::<BullseyeDetail>::default();
+    cot.detail.bullseye = Bullseye {
+        title: title.to_string(),
+        ..cot.detail.bullseye
+    };
+    cot.detail.contact.callsign = title.to_string();
+    cot.point = center;
+    cot
+}
+
+/// Create a MEDEVAC request for a pickup site at `point`, sent by `callsign`.
+///
+/// If `medevac` has no title, one is generated from the current time as ATAK does, e.g.
+/// `MED.161950`.
+pub fn medevac(callsign: &str, point: Point, mut medevac: Medevac) -> Cot<MedevacDetail> {
+    if medevac.title.is_none() {
+        medevac.title = Some(format!("MED.{}", Utc::now().format("%d%H%M")));
+    }
+    let detail = MedevacDetail {
+        medevac,
+        contact: Some(Contact {
+            callsign: callsign.to_string(),
+            ..Default::default()
+        }),
+        remarks: None,
+    };
+    new_cot(
+        COT_TYPE_MEDEVAC,
+        "h-g-i-g-o",
+        Duration::days(1),
+        detail,
+        point,
+    )
+}
+
+/// Event uid ATAK uses for all emergency events from the user with the given uid.
+fn emergency_uid(user_uid: &str) -> String {
+    format!("{}-9-1-1", user_uid)
+}
+
+/// Create an emergency alert raised by the user with the given uid and callsign, at `point`.
+pub fn emergency(
+    user_uid: &str,
+    callsign: &str,
+    kind: EmergencyType,
+    point: Point,
+) -> Cot<EmergencyDetail> {
+    let detail = EmergencyDetail {
+        link: Some(EmergencyLink {
+            uid: user_uid.to_string(),
+            cot_type: DEFAULT_COT_TYPE_SA.to_string(),
+            relation: "p-p".to_string(),
+        }),
+        contact: Some(Contact {
+            callsign: format!("{}-Alert", callsign),
+            ..Default::default()
+        }),
+        emergency: Emergency {
+            kind: Some(kind),
+            cancel: (kind == EmergencyType::Cancel).then_some(true),
+            callsign: callsign.to_string(),
+        },
+    };
+    let mut cot = new_cot(kind.cot_type(), "h-e", Duration::seconds(10), detail, point);
+    cot.uid = emergency_uid(user_uid);
+    cot
+}
+
+/// Create a cancellation of any emergency alert raised by the user with the given uid.
+pub fn cancel_emergency(user_uid: &str, callsign: &str, point: Point) -> Cot<EmergencyDetail> {
+    emergency(user_uid, callsign, EmergencyType::Cancel, point)
+}
+
+/// Create a GeoChat message to a chat room, e.g. [`ALL_CHAT_ROOMS`](super::detail::ALL_CHAT_ROOMS).
+pub fn chat_message(from: &ChatParticipant, room: &str, text: &str) -> Cot<TakChatDetail> {
+    new_chat(from, room, room, text)
+}
+
+/// Create a direct GeoChat message to another user.
+pub fn direct_message(
+    from: &ChatParticipant,
+    to: &ChatParticipant,
+    text: &str,
+) -> Cot<TakChatDetail> {
+    new_chat(from, &to.callsign, &to.uid, text)
+}
+
+/// Create a reply to a GeoChat message: a direct message to the sender if `incoming` was sent
+/// directly to us, otherwise a message to the same room.
+pub fn chat_reply(
+    incoming: &Cot<TakChatDetail>,
+    from: &ChatParticipant,
+    text: &str,
+) -> Cot<TakChatDetail> {
+    let chat = &incoming.detail.chat;
+    match incoming.detail.is_direct_to(&from.uid) {
+        true => {
+            let to = ChatParticipant::new(incoming.detail.sender_uid(), &chat.sender_callsign);
+            direct_message(from, &to, text)
+        }
+        false => new_chat(from, &chat.chatroom, &chat.id, text),
+    }
+}
+
+/// Create a delivery or read receipt for `message`, sent by its recipient `from`.
+pub fn chat_receipt(
+    message: &Cot<TakChatDetail>,
+    from: &ChatParticipant,
+    kind: ChatReceiptKind,
+) -> Cot<TakChatReceiptDetail> {
+    let detail = TakChatReceiptDetail {
+        chat: message.detail.chat.clone(),
+        link: Some(ChatLink {
+            uid: from.uid.clone(),
+            cot_type: DEFAULT_COT_TYPE_SA.to_string(),
+            relation: "p-p".to_string(),
+        }),
+    };
+    let mut cot = new_cot(
+        kind.cot_type(),
+        "h-g-i-g-o",
+        Duration::days(1),
+        detail,
+        Point::from_lat_lon(0.0, 0.0),
+    );
+    if let Some(message_id) = &message.detail.chat.message_id {
+        cot.uid = message_id.clone();
+    }
+    cot
+}
+
+fn new_chat(from: &ChatParticipant, room: &str, room_id: &str, text: &str) -> Cot<TakChatDetail> {
+    let message_id = uuid::Uuid::new_v4().to_string();
+    let detail = TakChatDetail {
+        chat: Chat {
+            parent: Some("RootContactGroup".to_string()),
+            group_owner: Some(false),
+            message_id: Some(message_id.clone()),
+            chatroom: room.to_string(),
+            id: room_id.to_string(),
+            sender_callsign: from.callsign.clone(),
+            chatgrp: ChatGroup {
+                uid0: from.uid.clone(),
+                uid1: room_id.to_string(),
+                id: room_id.to_string(),
+            },
+        },
+        link: Some(ChatLink {
+            uid: from.uid.clone(),
+            cot_type: DEFAULT_COT_TYPE_SA.to_string(),
+            relation: "p-p".to_string(),
+        }),
+        server_destination: None,
+        remarks: ChatRemarks {
+            source: format!("BAO.F.ATAK.{}", from.uid),
+            to: Some(room_id.to_string()),
+            time: Timestamp::now(),
+            text: text.to_string(),
+        },
+    };
+    let mut cot = new_cot(
+        COT_TYPE_CHAT,
+        "h-g-i-g-o",
+        Duration::days(1),
+        detail,
+        Point::from_lat_lon(0.0, 0.0),
+    );
+    cot.uid = format!("GeoChat.{}.{}.{}", from.uid, room_id, message_id);
+    cot.detail.remarks.time = cot.time.clone();
+    cot
+}
+
+/// Mean of the given (non-empty) points' coordinates.
+fn centroid(points: &[Point]) -> Point {
+    let n = points.len() as f64;
+    let lat = points.iter().map(|p| p.lat).sum::<f64>() / n;
+    let lon = points.iter().map(|p| p.lon).sum::<f64>() / n;
+    Point::from_lat_lon(lat, lon)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::tak::detail::ALL_CHAT_ROOMS;
+
+    use super::*;
+
+    #[test]
+    fn test_create_sa() {
+        let mut cot = Cot::<TakSaDetail>::default();
+        cot.detail.contact.callsign = "TRUCK 1".to_string();
+        let xml = quick_xml::se::to_string(&cot).unwrap();
+        assert!(xml.contains(r#"<__group name="Cyan" role="Team Member"/>"#));
+        let cot1: Cot<TakSaDetail> = quick_xml::de::from_str(&xml).unwrap();
+        assert_eq!(cot.detail, cot1.detail);
+        let detected = crate::tak::detect::detect_tak_cot_type(&xml).unwrap();
+        assert_eq!(detected.cot_type, crate::tak::detect::TakCotType::Sa);
+    }
+
+    #[test]
+    fn test_create_emergency() {
+        let point = Point::from_lat_lon(38.0, -77.0);
+        let alert = emergency(
+            "ANDROID-1",
+            "HOPE",
+            EmergencyType::RingTheBell,
+            point.clone(),
+        );
+        assert_eq!(alert.cot_type, "b-a-o-pan");
+        assert_eq!(alert.detail.originator_uid(), Some("ANDROID-1"));
+        let cancel = cancel_emergency("ANDROID-1", "HOPE", point);
+        assert_eq!(cancel.uid, alert.uid);
+        assert!(cancel.detail.is_cancel());
+        let xml = quick_xml::se::to_string(&cancel).unwrap();
+        assert!(xml.contains(r#"<emergency type="Cancel" cancel="true">HOPE</emergency>"#));
+        let cancel1: Cot<EmergencyDetail> = quick_xml::de::from_str(&xml).unwrap();
+        assert_eq!(cancel.detail, cancel1.detail);
+    }
+
+    #[test]
+    fn test_create_medevac() {
+        let request = Medevac {
+            urgent: 1,
+            ambulatory: 1,
+            ..Default::default()
+        };
+        let cot = medevac("DUSTOFF 6", Point::from_lat_lon(38.0, -77.0), request);
+        assert_eq!(cot.cot_type, COT_TYPE_MEDEVAC);
+        assert!(cot.detail.medevac.title.is_some());
+        let text = cot.detail.nine_line(&cot.point);
+        assert!(text.contains("Line 2: DUSTOFF 6\n"));
+        assert!(text.contains("Line 3: 1A\n"));
+        let xml = quick_xml::se::to_string(&cot).unwrap();
+        let cot1: Cot<MedevacDetail> = quick_xml::de::from_str(&xml).unwrap();
+        assert_eq!(cot.detail, cot1.detail);
+    }
+
+    #[test]
+    fn test_create_route() {
+        let waypoints = [
+            Point::from_lat_lon(38.0, -77.0),
+            Point::from_lat_lon(38.01, -77.0),
+            Point::from_lat_lon(38.01, -77.01),
+        ];
+        let cot = route("Route 7", &waypoints);
+        assert_eq!(cot.cot_type, COT_TYPE_ROUTE);
+        assert_eq!(cot.detail.links.len(), 3);
+        assert_eq!(cot.detail.links[0].callsign, "Route 7 SP");
+        assert_eq!(cot.detail.links[2].callsign, "CP2");
+        let xml = quick_xml::se::to_string(&cot).unwrap();
+        let cot1: Cot<TakRouteDetail> = quick_xml::de::from_str(&xml).unwrap();
+        assert_eq!(cot.detail, cot1.detail);
+    }
+
+    #[test]
+    fn test_create_circle() {
+        let cot = circle("Ring 1", Point::from_lat_lon(38.0, -77.0), 500.0);
+        assert_eq!(cot.cot_type, COT_TYPE_SHAPE_CIRCLE);
+        assert_eq!(cot.detail.shape.ellipse.major, 500.0);
+        assert_eq!(cot.detail.contact.callsign, "Ring 1");
+        let style_link = cot.detail.shape.link.as_ref().unwrap();
+        assert_eq!(style_link.uid, format!("{}.Style", cot.uid));
+        let xml = quick_xml::se::to_string(&cot).unwrap();
+        let cot1: Cot<TakShapeDetail> = quick_xml::de::from_str(&xml).unwrap();
+        assert_eq!(cot.detail, cot1.detail);
+    }
+
+    #[test]
+    fn test_create_polygon() {
+        let vertices = [
+            Point::from_lat_lon(38.0, -77.0),
+            Point::from_lat_lon(38.01, -77.0),
+            Point::from_lat_lon(38.01, -77.01),
+        ];
+        let cot = polygon("No-go 1", &vertices);
+        assert_eq!(cot.cot_type, COT_TYPE_SHAPE_FREE);
+        assert_eq!(cot.detail.links.len(), 4);
+        assert!(cot.detail.is_closed());
+        assert!(cot.detail.fill_color.is_some());
+        let xml = quick_xml::se::to_string(&cot).unwrap();
+        let cot1: Cot<TakPolygonDetail> = quick_xml::de::from_str(&xml).unwrap();
+        assert_eq!(cot.detail, cot1.detail);
+
+        let line = polyline("Line 1", &vertices, false);
+        assert_eq!(line.detail.links.len(), 3);
+        assert!(!line.detail.is_closed());
+        assert!(line.detail.fill_color.is_none());
+    }
+
+    #[test]
+    fn test_create_rectangle() {
+        let cot = rectangle(
+            "Box 1",
+            &Point::from_lat_lon(38.0, -77.0),
+            &Point::from_lat_lon(38.01, -77.01),
+        );
+        assert_eq!(cot.cot_type, COT_TYPE_SHAPE_RECTANGLE);
+        let [nw, _, se, _] = cot.detail.corners().unwrap();
+        assert_eq!(nw, LinkPoint::new(38.01, -77.01));
+        assert_eq!(se, LinkPoint::new(38.0, -77.0));
+        assert!((cot.point.lat - 38.005).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_create_telestration() {
+        let strokes = [
+            polyline(
+                "Freehand 1",
+                &[
+                    Point::from_lat_lon(38.0, -77.0),
+                    Point::from_lat_lon(38.01, -77.0),
+                ],
+                false,
+            ),
+            polyline(
+                "Freehand 1",
+                &[
+                    Point::from_lat_lon(38.02, -77.0),
+                    Point::from_lat_lon(38.03, -77.0),
+                ],
+                false,
+            ),
+        ];
+        let cot = telestration("Freehand 1", &strokes).unwrap();
+        assert_eq!(cot.cot_type, COT_TYPE_SHAPE_TELESTRATION);
+        assert!((cot.point.lat - 38.015).abs() < 1e-9);
+
+        let xml = quick_xml::se::to_string(&cot).unwrap();
+        let cot1: Cot<TakTelestrationDetail> = quick_xml::de::from_str(&xml).unwrap();
+        let parsed = cot1.detail.strokes().unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[1].uid, strokes[1].uid);
+        assert_eq!(parsed[1].detail, strokes[1].detail);
+    }
+
+    #[test]
+    fn test_create_range_bearing() {
+        let start = Point::from_lat_lon(38.0, -77.0);
+        let cot = range_bearing("R&B 1", start.clone(), 1000.0, 90.0);
+        assert_eq!(cot.cot_type, COT_TYPE_RANGE_BEARING);
+        let end = cot.detail.end_point(&cot.point, 0.0, 0.0);
+        assert!((start.distance_to(&end) - 1000.0).abs() < 1e-6);
+        assert!(end.lon > start.lon);
+        let xml = quick_xml::se::to_string(&cot).unwrap();
+        let cot1: Cot<RangeBearingDetail> = quick_xml::de::from_str(&xml).unwrap();
+        assert_eq!(cot.detail, cot1.detail);
+    }
+
+    #[test]
+    fn test_create_bullseye() {
+        let cot = bullseye("Bullseye 1", Point::from_lat_lon(38.0, -77.0));
+        assert_eq!(cot.cot_type, COT_TYPE_BULLSEYE);
+        assert_eq!(cot.detail.bullseye.bullseye_uid, cot.uid);
+        assert_eq!(cot.detail.bullseye.title, "Bullseye 1");
+        let xml = quick_xml::se::to_string(&cot).unwrap();
+        let cot1: Cot<BullseyeDetail> = quick_xml::de::from_str(&xml).unwrap();
+        assert_eq!(cot.detail, cot1.detail);
+    }
+
+    #[test]
+    fn test_create_chat() {
+        let bot = ChatParticipant::new("bot-1", "Bot");
+        let op = ChatParticipant::new("ANDROID-1", "HOPE");
+        let cot = chat_message(&bot, ALL_CHAT_ROOMS, "hello all");
+        assert_eq!(cot.cot_type, COT_TYPE_CHAT);
+        assert!(cot.uid.starts_with("GeoChat.bot-1.All Chat Rooms."));
+        assert_eq!(cot.detail.chat.chatgrp.uid1, ALL_CHAT_ROOMS);
+        let xml = quick_xml::se::to_string(&cot).unwrap();
+        let cot1: Cot<TakChatDetail> = quick_xml::de::from_str(&xml).unwrap();
+        assert_eq!(cot.detail.chat, cot1.detail.chat);
+        assert_eq!(cot.detail.text(), cot1.detail.text());
+
+        // Replies go to the room, or directly to the sender
+        let reply = chat_reply(&cot, &op, "hi bot");
+        assert_eq!(reply.detail.chat.id, ALL_CHAT_ROOMS);
+        let dm = direct_message(&op, &bot, "status?");
+        assert!(dm.detail.is_direct_to("bot-1"));
+        let reply = chat_reply(&dm, &bot, "all good");
+        assert!(reply.detail.is_direct_to("ANDROID-1"));
+        assert_eq!(reply.detail.chat.chatroom, "HOPE");
+        assert_eq!(reply.detail.text(), "all good");
+    }
 }
@@ -1,12 +1,43 @@
-use crate::base::serialize_date;
-use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+//! Type definitions for CoT detail sections for TAK messages.
+//!
+//! Limited message types supported so far.
 
-use crate::base::deserialize_date;
+use std::fmt;
+use std::str::FromStr;
 
-/// Type definitions for CoT detail sections for TAK messages.
-///
-/// Limited message types supported so far.
+use crate::base::{Point, UNKNOWN_VALUE};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::time::Timestamp;
+use crate::Error;
+
+use super::milsym::Sidc;
+
+mod bullseye;
+mod chat;
+mod color;
+mod emergency;
+mod geofence;
+mod medevac;
+mod polygon;
+mod range_bearing;
+mod route;
+mod sa;
+mod shape;
+mod telestration;
+
+pub use bullseye::*;
+pub use chat::*;
+pub use color::*;
+pub use emergency::*;
+pub use geofence::*;
+pub use medevac::*;
+pub use polygon::*;
+pub use range_bearing::*;
+pub use route::*;
+pub use sa::*;
+pub use shape::*;
+pub use telestration::*;
 
 /// `<detail>` section for a Marker message, with reasonable defaults to put a dot on a map (i.e.
 /// when sent to TAK).
@@ -25,30 +56,37 @@ pub struct TakMarkerDetail {
     pub precisionlocation: PrecisionLocation,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub usericon: Option<UserIcon>,
+    #[serde(rename = "__milsym", skip_serializing_if = "Option::is_none")]
+    pub milsym: Option<MilSym>,
 }
 
 // TODO move these common definitions
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Status {
-    #[serde(rename = "@readiness")]
+    #[serde(rename = "@readiness", default = "default_readiness")]
     pub readiness: bool,
+    /// Battery level in percent, sent with position reports.
+    #[serde(rename = "@battery", skip_serializing_if = "Option::is_none")]
+    pub battery: Option<u8>,
 }
 impl Default for Status {
     fn default() -> Self {
-        Status { readiness: true }
+        Status {
+            readiness: true,
+            battery: None,
+        }
     }
 }
+fn default_readiness() -> bool {
+    true
+}
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Link {
     #[serde(rename = "@uid")]
     uid: String,
-    #[serde(
-        rename = "@production_time",
-        serialize_with = "serialize_date",
-        deserialize_with = "deserialize_date"
-    )]
-    pub production_time: DateTime<Utc>,
+    #[serde(rename = "@production_time")]
+    pub production_time: Timestamp,
     #[serde(rename = "@type")]
     pub cot_type: String,
     #[serde(rename = "@parent_callsign")]
@@ -92,7 +130,7 @@ pub struct Remarks {
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Color {
     #[serde(rename = "@argb")]
-    pub argb: i32,
+    pub argb: TakColor,
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
@@ -133,7 +171,238 @@ impl Default for PrecisionLocation {
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct UserIcon {
     #[serde(rename = "@iconsetpath")]
-    iconsetpath: String,
+    pub iconsetpath: IconPath,
+}
+
+impl From<IconPath> for UserIcon {
+    fn from(iconsetpath: IconPath) -> Self {
+        Self { iconsetpath }
+    }
+}
+
+/// Prefix of icon paths for MIL-STD-2525B symbols.
+pub const ICON_PATH_2525B: &str = "COT_MAPPING_2525B";
+
+/// Prefix of icon paths for spot map markers.
+pub const ICON_PATH_SPOTMAP: &str = "COT_MAPPING_SPOTMAP";
+
+/// Parsed `iconsetpath` of a [`UserIcon`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum IconPath {
+    /// MIL-STD-2525B symbol for a CoT type, e.g. `COT_MAPPING_2525B/a-u/a-u-G`. Use
+    /// [`IconPath::mil2525b()`] to create one with the affiliation group ATAK expects.
+    Mil2525b { group: String, cot_type: String },
+    /// Spot map marker of a given color, e.g. `COT_MAPPING_SPOTMAP/b-m-p-s-m/-65536`.
+    SpotMap { cot_type: String, color: TakColor },
+    /// Icon from a user-installed iconset, e.g.
+    /// `f7f71666-8b28-4b57-9fbb-e38e61d33b79/Google/hiker.png`.
+    Custom {
+        iconset_uid: String,
+        group: String,
+        filename: String,
+    },
+    /// Any other path, kept verbatim.
+    Other(String),
+}
+
+impl IconPath {
+    /// MIL-STD-2525B symbol path for a CoT type.
+    pub fn mil2525b(cot_type: &str) -> Self {
+        // Symbols are grouped by affiliation, e.g. `a-u`.
+        let group: Vec<&str> = cot_type.splitn(3, '-').take(2).collect();
+        IconPath::Mil2525b {
+            group: group.join("-"),
+            cot_type: cot_type.to_string(),
+        }
+    }
+}
+
+impl FromStr for IconPath {
+    type Err = Error;
+
+    /// Parse an icon path. Paths which don't match a known form are returned as
+    /// [`IconPath::Other`], so this only fails for empty paths.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(Error::BadField("empty iconsetpath".into()));
+        }
+        let parts: Vec<&str> = s.splitn(3, '/').collect();
+        let path = match parts[..] {
+            [ICON_PATH_2525B, group, cot_type] => IconPath::Mil2525b {
+                group: group.to_string(),
+                cot_type: cot_type.to_string(),
+            },
+            [ICON_PATH_SPOTMAP, cot_type, color] => match color.parse() {
+                Ok(argb) => IconPath::SpotMap {
+                    cot_type: cot_type.to_string(),
+                    color: TakColor::from_i32(argb),
+                },
+                Err(_) => IconPath::Other(s.to_string()),
+            },
+            [iconset_uid, group, filename]
+                if !iconset_uid.starts_with("COT_MAPPING_") && !filename.contains('/') =>
+            {
+                IconPath::Custom {
+                    iconset_uid: iconset_uid.to_string(),
+                    group: group.to_string(),
+                    filename: filename.to_string(),
+                }
+            }
+            _ => IconPath::Other(s.to_string()),
+        };
+        Ok(path)
+    }
+}
+
+impl fmt::Display for IconPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IconPath::Mil2525b { group, cot_type } => {
+                write!(f, "{}/{}/{}", ICON_PATH_2525B, group, cot_type)
+            }
+            IconPath::SpotMap { cot_type, color } => {
+                write!(f, "{}/{}/{}", ICON_PATH_SPOTMAP, cot_type, color.to_i32())
+            }
+            IconPath::Custom {
+                iconset_uid,
+                group,
+                filename,
+            } => write!(f, "{}/{}/{}", iconset_uid, group, filename),
+            IconPath::Other(path) => write!(f, "{}", path),
+        }
+    }
+}
+
+impl Serialize for IconPath {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for IconPath {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// `<__milsym>` element, giving a marker's MIL-STD-2525 symbol code explicitly.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct MilSym {
+    /// SIDC, e.g. `SFGPUCI--------`.
+    #[serde(rename = "@id")]
+    pub id: String,
+}
+
+impl MilSym {
+    pub fn sidc(&self) -> Result<Sidc, Error> {
+        self.id.parse()
+    }
+}
+
+impl From<&Sidc> for MilSym {
+    fn from(sidc: &Sidc) -> Self {
+        Self {
+            id: sidc.to_string(),
+        }
+    }
+}
+
+/// Element with a single `value` attribute, e.g. `<strokeWeight value='3.0'/>`.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct ValueElement<T> {
+    #[serde(rename = "@value")]
+    pub value: T,
+}
+
+impl<T> From<T> for ValueElement<T> {
+    fn from(value: T) -> Self {
+        Self { value }
+    }
+}
+
+/// Element without attributes or content, e.g. `<archive/>`, which marks an item for ATAK to
+/// persist.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+pub struct Empty {}
+
+/// A `lat,lon[,hae]` coordinate string, as used by the `point` attribute of `<link>` elements in
+/// routes and shapes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LinkPoint {
+    pub lat: f64,
+    pub lon: f64,
+    pub hae: Option<f64>,
+}
+
+impl LinkPoint {
+    pub fn new(lat: f64, lon: f64) -> Self {
+        Self {
+            lat,
+            lon,
+            hae: None,
+        }
+    }
+
+    /// Convert to a base [`Point`], with unknown error values.
+    pub fn to_point(&self) -> Point {
+        let mut point = Point::from_lat_lon(self.lat, self.lon);
+        if let Some(hae) = self.hae {
+            point.hae = hae as f32;
+        }
+        point
+    }
+}
+
+impl From<&Point> for LinkPoint {
+    fn from(point: &Point) -> Self {
+        let hae = (point.hae != UNKNOWN_VALUE).then_some(point.hae as f64);
+        Self {
+            lat: point.lat,
+            lon: point.lon,
+            hae,
+        }
+    }
+}
+
+impl FromStr for LinkPoint {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(',').map(|p| p.trim().parse::<f64>());
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(Ok(lat)), Some(Ok(lon)), None, None) => Ok(Self::new(lat, lon)),
+            (Some(Ok(lat)), Some(Ok(lon)), Some(Ok(hae)), None) => Ok(Self {
+                lat,
+                lon,
+                hae: Some(hae),
+            }),
+            _ => Err(Error::BadField("link point must be 'lat,lon[,hae]'".into())),
+        }
+    }
+}
+
+impl fmt::Display for LinkPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.lat, self.lon)?;
+        if let Some(hae) = self.hae {
+            write!(f, ",{}", hae)?;
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for LinkPoint {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for LinkPoint {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
 }
 
 #[cfg(test)]
@@ -151,4 +420,53 @@ mod test {
         let marker: Cot<TakMarkerDetail> = quick_xml::de::from_str(&xml_text).unwrap();
         assert_eq!(marker.version, "2.0");
     }
+
+    #[test]
+    fn test_icon_path() {
+        let examples = [
+            ("COT_MAPPING_2525B/a-u/a-u-G", IconPath::mil2525b("a-u-G")),
+            (
+                "COT_MAPPING_2525B/a-f-G-U/a-f-G-U-C",
+                IconPath::Mil2525b {
+                    group: "a-f-G-U".to_string(),
+                    cot_type: "a-f-G-U-C".to_string(),
+                },
+            ),
+            (
+                "COT_MAPPING_SPOTMAP/b-m-p-s-m/-65536",
+                IconPath::SpotMap {
+                    cot_type: "b-m-p-s-m".to_string(),
+                    color: TakColor::RED,
+                },
+            ),
+            (
+                "f7f71666-8b28-4b57-9fbb-e38e61d33b79/Google/hiker.png",
+                IconPath::Custom {
+                    iconset_uid: "f7f71666-8b28-4b57-9fbb-e38e61d33b79".to_string(),
+                    group: "Google".to_string(),
+                    filename: "hiker.png".to_string(),
+                },
+            ),
+            (
+                "COT_MAPPING_SPOTMAP/b-m-p-s-m/LABEL",
+                IconPath::Other("COT_MAPPING_SPOTMAP/b-m-p-s-m/LABEL".to_string()),
+            ),
+        ];
+        for (text, path) in examples {
+            assert_eq!(text.parse::<IconPath>().unwrap(), path);
+            assert_eq!(path.to_string(), text);
+        }
+        assert!("".parse::<IconPath>().is_err());
+    }
+
+    #[test]
+    fn test_link_point() {
+        let p: LinkPoint = "38.84335305982451,-77.05440032542333".parse().unwrap();
+        assert_eq!(p, LinkPoint::new(38.84335305982451, -77.05440032542333));
+        assert_eq!(p.to_string(), "38.84335305982451,-77.05440032542333");
+        let p: LinkPoint = "1.5, 2.5, 100".parse().unwrap();
+        assert_eq!(p.hae, Some(100.0));
+        assert!("1.5".parse::<LinkPoint>().is_err());
+        assert!("a,b".parse::<LinkPoint>().is_err());
+    }
 }
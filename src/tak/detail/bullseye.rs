@@ -0,0 +1,285 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::de::{self, IgnoredAny, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::base::Point;
+use crate::Error;
+
+use super::{BearingUnits, Contact, Empty, NorthRef, PrecisionLocation, RangeUnits, Remarks};
+
+/// `<detail>` section for a bullseye (`u-r-b-bullseye`), centered on the event's `point`.
+///
+/// ATAK writes `<archive/>` twice, before `<bullseye>` and after `<remarks>`; both are read into
+/// `archive`, which is written once.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct BullseyeDetail {
+    pub bullseye: Bullseye,
+    pub contact: Contact,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remarks: Option<Remarks>,
+    /// `<archive/>`: receivers persist the bullseye.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archive: Option<Empty>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub precisionlocation: Option<PrecisionLocation>,
+}
+
+impl<'de> Deserialize<'de> for BullseyeDetail {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        struct DetailVisitor;
+
+        impl<'de> Visitor<'de> for DetailVisitor {
+            type Value = BullseyeDetail;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("bullseye detail")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let (mut bullseye, mut contact) = (None, None);
+                let mut detail = BullseyeDetail::default();
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        "bullseye" => bullseye = Some(map.next_value()?),
+                        "contact" => contact = Some(map.next_value()?),
+                        "remarks" => detail.remarks = Some(map.next_value()?),
+                        "archive" => detail.archive = Some(map.next_value()?),
+                        "precisionlocation" => detail.precisionlocation = Some(map.next_value()?),
+                        _ => {
+                            map.next_value::<IgnoredAny>()?;
+                        }
+                    }
+                }
+                detail.bullseye = bullseye.ok_or_else(|| de::Error::missing_field("bullseye"))?;
+                detail.contact = contact.ok_or_else(|| de::Error::missing_field("contact"))?;
+                Ok(detail)
+            }
+        }
+
+        d.deserialize_map(DetailVisitor)
+    }
+}
+
+/// `<bullseye>` element.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Bullseye {
+    /// Whether bearings are expressed in mils instead of degrees.
+    #[serde(rename = "@mils")]
+    pub mils: bool,
+    /// Radius of the bullseye (and spacing of range rings), in meters.
+    #[serde(rename = "@distance")]
+    pub distance: f64,
+    #[serde(rename = "@bearingRef", with = "north_ref_letter")]
+    pub bearing_ref: NorthRef,
+    #[serde(rename = "@bullseyeUID")]
+    pub bullseye_uid: String,
+    /// Units used when expressing ranges relative to the bullseye.
+    #[serde(rename = "@distanceUnits", with = "range_units_abbrev")]
+    pub distance_units: RangeUnits,
+    /// Whether bearings are expressed from the edge toward the center, instead of from the
+    /// center outward.
+    #[serde(rename = "@edgeToCenter")]
+    pub edge_to_center: bool,
+    #[serde(rename = "@rangeRingVisible")]
+    pub range_ring_visible: bool,
+    #[serde(rename = "@title")]
+    pub title: String,
+    #[serde(rename = "@hasRangeRings")]
+    pub has_range_rings: bool,
+}
+
+impl Default for Bullseye {
+    fn default() -> Self {
+        Self {
+            mils: false,
+            distance: 1000.0,
+            bearing_ref: NorthRef::Magnetic,
+            bullseye_uid: String::new(),
+            distance_units: RangeUnits::Meters,
+            edge_to_center: false,
+            range_ring_visible: false,
+            title: String::new(),
+            has_range_rings: false,
+        }
+    }
+}
+
+/// A position expressed relative to a bullseye, in the bullseye's units and north reference.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BullseyeCall {
+    pub bearing: f64,
+    pub bearing_units: BearingUnits,
+    pub range: f64,
+    pub range_units: RangeUnits,
+}
+
+impl fmt::Display for BullseyeCall {
+    /// Format as `bearing/range`, e.g. `045/12.3`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.bearing_units {
+            BearingUnits::Mils => {
+                let b = self.bearing.round() % super::MILS_PER_CIRCLE;
+                write!(f, "{:04}/{:.1}", b as u32, self.range)
+            }
+            _ => {
+                let deg = self.bearing_units.to_degrees(self.bearing);
+                write!(f, "{:03}/{:.1}", deg.round() as u32 % 360, self.range)
+            }
+        }
+    }
+}
+
+impl Bullseye {
+    pub fn bearing_units(&self) -> BearingUnits {
+        match self.mils {
+            true => BearingUnits::Mils,
+            false => BearingUnits::Degrees,
+        }
+    }
+
+    /// Express `target` as bearing and range from the bullseye at `center`.
+    ///
+    /// `declination` and `convergence` are used for magnetic and grid bearing references, see
+    /// [`NorthRef::to_true()`].
+    pub fn call(
+        &self,
+        center: &Point,
+        target: &Point,
+        declination: f64,
+        convergence: f64,
+    ) -> BullseyeCall {
+        let true_bearing = match self.edge_to_center {
+            true => target.bearing_to(center),
+            false => center.bearing_to(target),
+        };
+        let bearing = self
+            .bearing_ref
+            .from_true(true_bearing, declination, convergence);
+        let bearing_units = self.bearing_units();
+        BullseyeCall {
+            bearing: bearing_units.from_degrees(bearing),
+            bearing_units,
+            range: self.distance_units.from_meters(center.distance_to(target)),
+            range_units: self.distance_units,
+        }
+    }
+
+    /// Like [`Bullseye::call()`], with the magnetic declination at `time` and the grid convergence
+    /// at `center`.
+    pub fn call_at(
+        &self,
+        center: &Point,
+        target: &Point,
+        time: &DateTime<Utc>,
+    ) -> Result<BullseyeCall, Error> {
+        let declination = center.declination(time);
+        let convergence = center.grid_convergence()?;
+        Ok(self.call(center, target, declination, convergence))
+    }
+}
+
+/// `bearingRef` is serialized as `T`, `M` or `G`.
+mod north_ref_letter {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(north_ref: &NorthRef, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(match north_ref {
+            NorthRef::True => "T",
+            NorthRef::Magnetic => "M",
+            NorthRef::Grid => "G",
+        })
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<NorthRef, D::Error> {
+        match String::deserialize(d)?.as_str() {
+            "T" => Ok(NorthRef::True),
+            "M" => Ok(NorthRef::Magnetic),
+            "G" => Ok(NorthRef::Grid),
+            other => Err(serde::de::Error::custom(format!(
+                "unknown bearing reference '{}'",
+                other
+            ))),
+        }
+    }
+}
+
+/// `distanceUnits` is serialized as a unit abbreviation.
+mod range_units_abbrev {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(units: &RangeUnits, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(units.abbreviation())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<RangeUnits, D::Error> {
+        let s = String::deserialize(d)?;
+        RangeUnits::from_abbreviation(&s)
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown distance units '{}'", s)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::base::Cot;
+
+    use super::*;
+
+    #[test]
+    fn test_bullseye_example() {
+        let xml_path = format!(
+            "{}/src/tak/examples/range-bearing-bullseye.cot",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let xml_text = std::fs::read_to_string(xml_path).unwrap();
+        let cot: Cot<BullseyeDetail> = quick_xml::de::from_str(&xml_text).unwrap();
+        let bullseye = &cot.detail.bullseye;
+        assert_eq!(bullseye.bearing_ref, NorthRef::Magnetic);
+        assert_eq!(bullseye.distance_units, RangeUnits::Meters);
+        assert_eq!(bullseye.title, "Bullseye 1");
+        assert_eq!(cot.detail.archive, Some(Empty {}));
+
+        let xml = quick_xml::se::to_string(&cot).unwrap();
+        assert!(
+            xml.contains("<remarks/><archive/><precisionlocation"),
+            "{}",
+            xml
+        );
+        assert!(xml.contains(r#"bearingRef="M""#));
+        let cot1: Cot<BullseyeDetail> = quick_xml::de::from_str(&xml).unwrap();
+        assert_eq!(cot, cot1);
+    }
+
+    #[test]
+    fn test_bullseye_call() {
+        let center = Point::from_lat_lon(38.0, -77.0);
+        let target = center.destination(100.0, 2.0 * 1852.0);
+        let mut bullseye = Bullseye {
+            bearing_ref: NorthRef::True,
+            distance_units: RangeUnits::NauticalMiles,
+            ..Default::default()
+        };
+        let call = bullseye.call(&center, &target, 0.0, 0.0);
+        assert!((call.bearing - 100.0).abs() < 1e-6);
+        assert!((call.range - 2.0).abs() < 1e-6);
+        assert_eq!(call.to_string(), "100/2.0");
+
+        // 10 degrees west declination: magnetic bearing is larger
+        bullseye.bearing_ref = NorthRef::Magnetic;
+        bullseye.mils = true;
+        let call = bullseye.call(&center, &target, -10.0, 0.0);
+        assert!((call.bearing - 110.0 * 6400.0 / 360.0).abs() < 1e-6);
+        assert_eq!(call.to_string(), "1956/2.0");
+
+        bullseye.edge_to_center = true;
+        bullseye.mils = false;
+        let call = bullseye.call(&center, &target, -10.0, 0.0);
+        assert!((call.bearing - 290.0).abs() < 0.1, "{}", call.bearing);
+
+        let time = "2024-06-01T00:00:00Z".parse().unwrap();
+        let call = bullseye.call_at(&center, &target, &time).unwrap();
+        let expected = bullseye.call(&center, &target, center.declination(&time), 0.0);
+        assert_eq!(call, expected);
+    }
+}
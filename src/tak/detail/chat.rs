@@ -0,0 +1,205 @@
+use serde::{Deserialize, Serialize};
+
+use crate::time::Timestamp;
+
+/// Name and id of the chat room that all users are in.
+pub const ALL_CHAT_ROOMS: &str = "All Chat Rooms";
+
+/// `<detail>` section for a GeoChat message (`b-t-f`).
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct TakChatDetail {
+    #[serde(rename = "__chat")]
+    pub chat: Chat,
+    /// Link to the sender.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link: Option<ChatLink>,
+    #[serde(
+        rename = "__serverdestination",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub server_destination: Option<ServerDestination>,
+    pub remarks: ChatRemarks,
+}
+
+/// `<__chat>` element.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Chat {
+    #[serde(rename = "@parent", skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    #[serde(rename = "@groupOwner", skip_serializing_if = "Option::is_none")]
+    pub group_owner: Option<bool>,
+    #[serde(rename = "@messageId", skip_serializing_if = "Option::is_none")]
+    pub message_id: Option<String>,
+    /// Room name, or the recipient's callsign for direct messages.
+    #[serde(rename = "@chatroom")]
+    pub chatroom: String,
+    /// Room id, or the recipient's uid for direct messages.
+    #[serde(rename = "@id")]
+    pub id: String,
+    #[serde(rename = "@senderCallsign")]
+    pub sender_callsign: String,
+    pub chatgrp: ChatGroup,
+}
+
+/// `<chatgrp>` element, listing the participants: `uid0` is the sender, and `uid1` the room id
+/// or recipient uid.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ChatGroup {
+    #[serde(rename = "@uid0")]
+    pub uid0: String,
+    #[serde(rename = "@uid1")]
+    pub uid1: String,
+    #[serde(rename = "@id")]
+    pub id: String,
+}
+
+/// `<link>` to the sender of a message.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ChatLink {
+    #[serde(rename = "@uid")]
+    pub uid: String,
+    #[serde(rename = "@type")]
+    pub cot_type: String,
+    #[serde(rename = "@relation")]
+    pub relation: String,
+}
+
+/// `<__serverdestination>` element, added by ATAK with the sender's endpoint(s).
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ServerDestination {
+    #[serde(rename = "@destinations")]
+    pub destinations: String,
+}
+
+/// `<remarks>` element of a chat message, containing the message text.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ChatRemarks {
+    /// Sender, e.g. `BAO.F.ATAK.<sender uid>`.
+    #[serde(rename = "@source")]
+    pub source: String,
+    /// Room id, or the recipient's uid for direct messages.
+    #[serde(rename = "@to", skip_serializing_if = "Option::is_none")]
+    pub to: Option<String>,
+    #[serde(rename = "@time")]
+    pub time: Timestamp,
+    #[serde(rename = "$text", default)]
+    pub text: String,
+}
+
+/// `<detail>` section for a GeoChat delivery (`b-t-f-d`) or read (`b-t-f-r`) receipt.
+///
+/// The receipt's event uid is the original message id, and `chat` repeats the original message's
+/// `<__chat>` fields.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct TakChatReceiptDetail {
+    #[serde(rename = "__chatreceipt")]
+    pub chat: Chat,
+    /// Link to the user who received or read the message.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link: Option<ChatLink>,
+}
+
+impl TakChatReceiptDetail {
+    /// Id of the message this receipt is for.
+    pub fn message_id(&self) -> Option<&str> {
+        self.chat.message_id.as_deref()
+    }
+
+    /// uid of the user who sent this receipt.
+    pub fn receiver_uid(&self) -> &str {
+        match &self.link {
+            Some(link) => &link.uid,
+            None => &self.chat.chatgrp.uid1,
+        }
+    }
+}
+
+/// Kind of chat receipt, identified by CoT type.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ChatReceiptKind {
+    Delivered,
+    Read,
+}
+
+impl ChatReceiptKind {
+    pub fn cot_type(self) -> &'static str {
+        match self {
+            ChatReceiptKind::Delivered => "b-t-f-d",
+            ChatReceiptKind::Read => "b-t-f-r",
+        }
+    }
+
+    pub fn from_cot_type(cot_type: &str) -> Option<Self> {
+        match cot_type {
+            "b-t-f-d" => Some(ChatReceiptKind::Delivered),
+            "b-t-f-r" => Some(ChatReceiptKind::Read),
+            _ => None,
+        }
+    }
+}
+
+/// A chat sender or recipient.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChatParticipant {
+    pub uid: String,
+    pub callsign: String,
+}
+
+impl ChatParticipant {
+    pub fn new(uid: &str, callsign: &str) -> Self {
+        Self {
+            uid: uid.to_string(),
+            callsign: callsign.to_string(),
+        }
+    }
+}
+
+impl TakChatDetail {
+    pub fn text(&self) -> &str {
+        &self.remarks.text
+    }
+
+    pub fn sender_uid(&self) -> &str {
+        &self.chat.chatgrp.uid0
+    }
+
+    /// Whether this is a direct message to `uid`, rather than a message to a room.
+    pub fn is_direct_to(&self, uid: &str) -> bool {
+        self.chat.id == uid
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::base::Cot;
+
+    use super::*;
+
+    #[test]
+    fn test_chat_example() {
+        let xml_path = format!(
+            "{}/src/tak/examples/chat-all-rooms.cot",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let xml_text = std::fs::read_to_string(xml_path).unwrap();
+        let cot: Cot<TakChatDetail> = quick_xml::de::from_str(&xml_text).unwrap();
+        let chat = &cot.detail;
+        assert_eq!(chat.text(), "Moving to CP2 now");
+        assert_eq!(chat.sender_uid(), "ANDROID-589520ccfcd20f01");
+        assert_eq!(chat.chat.chatroom, ALL_CHAT_ROOMS);
+        assert_eq!(chat.chat.sender_callsign, "HOPE");
+        assert!(!chat.is_direct_to("ANDROID-1"));
+
+        let xml = quick_xml::se::to_string(&cot).unwrap();
+        let cot1: Cot<TakChatDetail> = quick_xml::de::from_str(&xml).unwrap();
+        assert_eq!(cot, cot1);
+    }
+
+    #[test]
+    fn test_receipt_kind() {
+        for kind in [ChatReceiptKind::Delivered, ChatReceiptKind::Read] {
+            assert_eq!(ChatReceiptKind::from_cot_type(kind.cot_type()), Some(kind));
+        }
+        assert_eq!(ChatReceiptKind::from_cot_type("b-t-f"), None);
+    }
+}
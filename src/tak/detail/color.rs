@@ -0,0 +1,201 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::Error;
+
+/// A TAK color: 32-bit ARGB, i.e. alpha in the most significant byte.
+///
+/// TAK sends colors as signed decimal integers, e.g. `<color argb='-65536'/>` (opaque red) or
+/// `<strokeColor value='-1'/>` (opaque white), which is how this type (de)serializes. The
+/// [`argb_hex`] module (de)serializes the 8 hex digit form used in ATAK's KML `<Style>` elements.
+///
+/// [`fmt::Display`] and [`FromStr`] use `#AARRGGBB`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct TakColor(u32);
+
+impl TakColor {
+    pub const WHITE: TakColor = TakColor::from_rgb(0xff, 0xff, 0xff);
+    pub const BLACK: TakColor = TakColor::from_rgb(0, 0, 0);
+    pub const RED: TakColor = TakColor::from_rgb(0xff, 0, 0);
+    pub const GREEN: TakColor = TakColor::from_rgb(0, 0xff, 0);
+    pub const BLUE: TakColor = TakColor::from_rgb(0, 0, 0xff);
+    pub const YELLOW: TakColor = TakColor::from_rgb(0xff, 0xff, 0);
+    pub const CYAN: TakColor = TakColor::from_rgb(0, 0xff, 0xff);
+    pub const MAGENTA: TakColor = TakColor::from_rgb(0xff, 0, 0xff);
+    pub const TRANSPARENT: TakColor = TakColor(0);
+
+    pub const fn from_argb(a: u8, r: u8, g: u8, b: u8) -> Self {
+        Self((a as u32) << 24 | (r as u32) << 16 | (g as u32) << 8 | b as u32)
+    }
+
+    /// Opaque color.
+    pub const fn from_rgb(r: u8, g: u8, b: u8) -> Self {
+        Self::from_argb(0xff, r, g, b)
+    }
+
+    pub const fn from_rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self::from_argb(a, r, g, b)
+    }
+
+    /// From the signed integer form TAK uses, e.g. `-65536`.
+    pub const fn from_i32(argb: i32) -> Self {
+        Self(argb as u32)
+    }
+
+    /// Signed integer form TAK uses, e.g. `-65536`.
+    pub const fn to_i32(self) -> i32 {
+        self.0 as i32
+    }
+
+    pub const fn argb(self) -> u32 {
+        self.0
+    }
+
+    pub const fn a(self) -> u8 {
+        (self.0 >> 24) as u8
+    }
+
+    pub const fn r(self) -> u8 {
+        (self.0 >> 16) as u8
+    }
+
+    pub const fn g(self) -> u8 {
+        (self.0 >> 8) as u8
+    }
+
+    pub const fn b(self) -> u8 {
+        self.0 as u8
+    }
+
+    /// Components in `[r, g, b, a]` order, e.g. for CSS `rgba()` or image buffers.
+    pub const fn to_rgba(self) -> [u8; 4] {
+        [self.r(), self.g(), self.b(), self.a()]
+    }
+
+    /// Same color with a different alpha.
+    pub const fn with_alpha(self, a: u8) -> Self {
+        Self::from_argb(a, self.r(), self.g(), self.b())
+    }
+
+    /// KML color string, `aabbggrr`, as in the KML specification.
+    ///
+    /// Note that ATAK writes the `<Style>` colors in its CoT shapes as ARGB hex instead, see
+    /// [`argb_hex`].
+    pub fn to_kml(self) -> String {
+        format!(
+            "{:02x}{:02x}{:02x}{:02x}",
+            self.a(),
+            self.b(),
+            self.g(),
+            self.r()
+        )
+    }
+
+    /// Parse a KML `aabbggrr` color string.
+    pub fn from_kml(s: &str) -> Result<Self, Error> {
+        let abgr = parse_hex(s)?;
+        let [a, b, g, r] = abgr.to_be_bytes();
+        Ok(Self::from_argb(a, r, g, b))
+    }
+}
+
+/// Parse 8 hex digits, or 6 for an opaque color.
+fn parse_hex(s: &str) -> Result<u32, Error> {
+    let value = u32::from_str_radix(s, 16)
+        .map_err(|_| Error::BadField(format!("invalid hex color {:?}", s).into()))?;
+    match s.len() {
+        8 => Ok(value),
+        6 => Ok(0xff00_0000 | value),
+        _ => Err(Error::BadField("hex color must have 6 or 8 digits".into())),
+    }
+}
+
+impl From<i32> for TakColor {
+    fn from(argb: i32) -> Self {
+        Self::from_i32(argb)
+    }
+}
+
+impl From<TakColor> for i32 {
+    fn from(color: TakColor) -> Self {
+        color.to_i32()
+    }
+}
+
+impl fmt::Display for TakColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:08X}", self.0)
+    }
+}
+
+impl FromStr for TakColor {
+    type Err = Error;
+
+    /// Parse `#AARRGGBB` or `#RRGGBB` (opaque).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s
+            .strip_prefix('#')
+            .ok_or(Error::BadField("color must start with '#'".into()))?;
+        parse_hex(hex).map(Self)
+    }
+}
+
+impl Serialize for TakColor {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i32(self.to_i32())
+    }
+}
+
+impl<'de> Deserialize<'de> for TakColor {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        i32::deserialize(deserializer).map(Self::from_i32)
+    }
+}
+
+/// Serde for a [`TakColor`] as 8 lowercase hex digits, ARGB byte order.
+///
+/// This is what ATAK writes in the `<color>` elements of its KML `<Style>`s (e.g. `ffff0000` for
+/// `strokeColor` `-65536`, red), even though real KML colors are `aabbggrr`.
+pub mod argb_hex {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use super::{parse_hex, TakColor};
+
+    pub fn serialize<S: Serializer>(color: &TakColor, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&format_args!("{:08x}", color.argb()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<TakColor, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        parse_hex(&s)
+            .map(TakColor)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_color_conversions() {
+        let red = TakColor::from(-65536);
+        assert_eq!(red, TakColor::RED);
+        assert_eq!(red.to_rgba(), [0xff, 0, 0, 0xff]);
+        assert_eq!(red.to_string(), "#FFFF0000");
+        assert_eq!(red.to_kml(), "ff0000ff");
+        assert_eq!(TakColor::from_kml("ff0000ff").unwrap(), red);
+
+        let fill = TakColor::from_i32(-1761607681);
+        assert_eq!(fill, TakColor::WHITE.with_alpha(0x96));
+        assert_eq!(fill.a(), 0x96);
+        assert_eq!(i32::from(fill), -1761607681);
+        assert_eq!("#96FFFFFF".parse::<TakColor>().unwrap(), fill);
+        assert_eq!("#00ff00".parse::<TakColor>().unwrap(), TakColor::GREEN);
+        assert!("00ff00".parse::<TakColor>().is_err());
+        assert!("#0ff".parse::<TakColor>().is_err());
+        assert_eq!(TakColor::from_rgba(1, 2, 3, 4).to_rgba(), [1, 2, 3, 4]);
+    }
+}
@@ -0,0 +1,161 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::Contact;
+
+/// `<detail>` section for an emergency alert, or the cancellation of one.
+///
+/// ATAK gives all emergency events from a user the same event uid, `<user uid>-9-1-1`, so that a
+/// cancel replaces the alert it clears.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct EmergencyDetail {
+    /// Link to the user who raised the alert.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link: Option<EmergencyLink>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contact: Option<Contact>,
+    pub emergency: Emergency,
+}
+
+impl EmergencyDetail {
+    /// uid of the user who raised the alert, if linked.
+    pub fn originator_uid(&self) -> Option<&str> {
+        self.link.as_ref().map(|l| l.uid.as_str())
+    }
+
+    pub fn is_cancel(&self) -> bool {
+        self.emergency.cancel == Some(true) || self.emergency.kind == Some(EmergencyType::Cancel)
+    }
+}
+
+/// `<link>` to the user who raised an alert.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct EmergencyLink {
+    #[serde(rename = "@uid")]
+    pub uid: String,
+    #[serde(rename = "@type")]
+    pub cot_type: String,
+    #[serde(rename = "@relation")]
+    pub relation: String,
+}
+
+/// `<emergency>` element. The text content is the callsign of the user in distress.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Emergency {
+    #[serde(rename = "@type", skip_serializing_if = "Option::is_none")]
+    pub kind: Option<EmergencyType>,
+    #[serde(rename = "@cancel", skip_serializing_if = "Option::is_none")]
+    pub cancel: Option<bool>,
+    #[serde(rename = "$text", default)]
+    pub callsign: String,
+}
+
+/// Kind of emergency alert, as named in the `<emergency type='..'>` attribute.
+///
+/// Each kind has its own CoT event type, see [`EmergencyType::cot_type()`]. ATAK sends
+/// cancellations as `b-a-o-can` with `<emergency cancel='true'>`. `b-a-o-c` is ATAK's custom
+/// alert, not a cancellation, even though it is sometimes documented as one. To check whether an
+/// event clears an alert, use [`EmergencyDetail::is_cancel()`] rather than the event type.
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum EmergencyType {
+    #[serde(rename = "911 Alert")]
+    NineOneOne,
+    #[serde(rename = "Ring The Bell")]
+    RingTheBell,
+    #[serde(rename = "Troops In Contact", alias = "In Contact")]
+    TroopsInContact,
+    #[serde(rename = "Geo-fence Breached")]
+    GeoFenceBreached,
+    Custom,
+    Cancel,
+}
+
+impl EmergencyType {
+    /// CoT event type used for this kind of alert.
+    pub fn cot_type(self) -> &'static str {
+        match self {
+            EmergencyType::NineOneOne => "b-a-o-tbl",
+            EmergencyType::RingTheBell => "b-a-o-pan",
+            EmergencyType::TroopsInContact => "b-a-o-opn",
+            EmergencyType::GeoFenceBreached => "b-a-g",
+            // Not a cancellation, see the type docs.
+            EmergencyType::Custom => "b-a-o-c",
+            EmergencyType::Cancel => "b-a-o-can",
+        }
+    }
+
+    pub fn from_cot_type(cot_type: &str) -> Option<Self> {
+        match cot_type {
+            "b-a-o-tbl" => Some(EmergencyType::NineOneOne),
+            "b-a-o-pan" => Some(EmergencyType::RingTheBell),
+            "b-a-o-opn" => Some(EmergencyType::TroopsInContact),
+            "b-a-g" => Some(EmergencyType::GeoFenceBreached),
+            "b-a-o-c" => Some(EmergencyType::Custom),
+            "b-a-o-can" => Some(EmergencyType::Cancel),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for EmergencyType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            EmergencyType::NineOneOne => "911 Alert",
+            EmergencyType::RingTheBell => "Ring The Bell",
+            EmergencyType::TroopsInContact => "Troops In Contact",
+            EmergencyType::GeoFenceBreached => "Geo-fence Breached",
+            EmergencyType::Custom => "Custom",
+            EmergencyType::Cancel => "Cancel",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::base::Cot;
+
+    use super::*;
+
+    #[test]
+    fn test_emergency_example() {
+        let xml_path = format!(
+            "{}/src/tak/examples/emergency-911.cot",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let xml_text = std::fs::read_to_string(xml_path).unwrap();
+        let cot: Cot<EmergencyDetail> = quick_xml::de::from_str(&xml_text).unwrap();
+        assert_eq!(
+            EmergencyType::from_cot_type(&cot.cot_type),
+            Some(EmergencyType::NineOneOne)
+        );
+        let emergency = &cot.detail.emergency;
+        assert_eq!(emergency.kind, Some(EmergencyType::NineOneOne));
+        assert_eq!(emergency.callsign, "HOPE");
+        assert_eq!(
+            cot.detail.originator_uid(),
+            Some("ANDROID-589520ccfcd20f01")
+        );
+        assert!(!cot.detail.is_cancel());
+
+        let xml = quick_xml::se::to_string(&cot).unwrap();
+        assert!(xml.contains(r#"type="911 Alert""#));
+        let cot1: Cot<EmergencyDetail> = quick_xml::de::from_str(&xml).unwrap();
+        assert_eq!(cot.detail, cot1.detail);
+    }
+
+    #[test]
+    fn test_emergency_cot_types() {
+        assert_eq!(
+            EmergencyType::from_cot_type("b-a-o-can"),
+            Some(EmergencyType::Cancel)
+        );
+        assert_eq!(EmergencyType::Cancel.cot_type(), "b-a-o-can");
+        assert_eq!(
+            EmergencyType::from_cot_type("b-a-o-c"),
+            Some(EmergencyType::Custom)
+        );
+        assert_eq!(EmergencyType::Custom.cot_type(), "b-a-o-c");
+    }
+}
@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+
+use super::{
+    Contact, Empty, PrecisionLocation, Remarks, Shape, TakColor, ValueElement, VertexLink,
+};
+
+/// `<detail>` section for a geofence: a shape with a `<__geofence>` element.
+///
+/// Circle and ellipse fences have a `<shape>`, while polygon and rectangle fences have their
+/// vertices as `<link point='lat,lon'/>` elements.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct GeoFenceDetail {
+    #[serde(rename = "link", default)]
+    pub links: Vec<VertexLink>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shape: Option<Shape>,
+    #[serde(rename = "__geofence")]
+    pub geofence: GeoFence,
+    #[serde(rename = "strokeColor", skip_serializing_if = "Option::is_none")]
+    pub stroke_color: Option<ValueElement<TakColor>>,
+    #[serde(rename = "strokeWeight", skip_serializing_if = "Option::is_none")]
+    pub stroke_weight: Option<ValueElement<f32>>,
+    #[serde(rename = "fillColor", skip_serializing_if = "Option::is_none")]
+    pub fill_color: Option<ValueElement<TakColor>>,
+    pub contact: Contact,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remarks: Option<Remarks>,
+    /// `<archive/>`: receivers persist the fence.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archive: Option<Empty>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub labels_on: Option<ValueElement<bool>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub precisionlocation: Option<PrecisionLocation>,
+}
+
+/// `<__geofence>` element.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct GeoFence {
+    #[serde(rename = "@elevationMonitored", default)]
+    pub elevation_monitored: bool,
+    /// Minimum monitored elevation (HAE) in meters.
+    #[serde(rename = "@minElevation", skip_serializing_if = "Option::is_none")]
+    pub min_elevation: Option<f64>,
+    #[serde(rename = "@monitor")]
+    pub monitor: GeoFenceMonitor,
+    #[serde(rename = "@trigger")]
+    pub trigger: GeoFenceTrigger,
+    #[serde(rename = "@tracking", default)]
+    pub tracking: bool,
+    /// Maximum monitored elevation (HAE) in meters.
+    #[serde(rename = "@maxElevation", skip_serializing_if = "Option::is_none")]
+    pub max_elevation: Option<f64>,
+    /// Radius in meters around the fence beyond which items are not monitored.
+    #[serde(rename = "@boundingSphere", skip_serializing_if = "Option::is_none")]
+    pub bounding_sphere: Option<f64>,
+}
+
+impl Default for GeoFence {
+    fn default() -> Self {
+        Self {
+            elevation_monitored: false,
+            min_elevation: None,
+            monitor: GeoFenceMonitor::All,
+            trigger: GeoFenceTrigger::Entry,
+            tracking: true,
+            max_elevation: None,
+            bounding_sphere: None,
+        }
+    }
+}
+
+/// Which items a geofence monitors.
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum GeoFenceMonitor {
+    All,
+    #[serde(rename = "TAKUsers")]
+    TakUsers,
+    Friendly,
+    Hostile,
+    /// A custom list of items, by uid.
+    Custom,
+}
+
+/// Which crossings of a geofence's boundary raise an alert.
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum GeoFenceTrigger {
+    Entry,
+    Exit,
+    Both,
+}
+
+#[cfg(test)]
+mod test {
+    use crate::base::Cot;
+
+    use super::*;
+
+    #[test]
+    fn test_geofence_example() {
+        let xml_path = format!(
+            "{}/src/tak/examples/geo-fence.cot",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let xml_text = std::fs::read_to_string(xml_path).unwrap();
+        let cot: Cot<GeoFenceDetail> = quick_xml::de::from_str(&xml_text).unwrap();
+        let fence = &cot.detail.geofence;
+        assert!(fence.elevation_monitored);
+        assert_eq!(fence.monitor, GeoFenceMonitor::All);
+        assert_eq!(fence.trigger, GeoFenceTrigger::Entry);
+        assert_eq!(fence.bounding_sphere, Some(75000.0));
+        assert_eq!(cot.detail.shape.as_ref().unwrap().ellipse.major, 297.72);
+        assert!(cot.detail.links.is_empty());
+        assert_eq!(cot.detail.archive, Some(Empty {}));
+
+        let xml = quick_xml::se::to_string(&cot).unwrap();
+        assert!(xml.contains("<remarks/><archive/><labels_on"), "{}", xml);
+        let cot1: Cot<GeoFenceDetail> = quick_xml::de::from_str(&xml).unwrap();
+        assert_eq!(cot, cot1);
+    }
+}
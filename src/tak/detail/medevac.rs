@@ -0,0 +1,436 @@
+use serde::{Deserialize, Serialize};
+
+use crate::base::Point;
+use crate::{coords, Error};
+
+use super::{Contact, Remarks};
+
+/// `<detail>` section for a MEDEVAC / CASEVAC request (`b-r-f-h-c`), as sent by ATAK's CASEVAC
+/// tool.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct MedevacDetail {
+    #[serde(rename = "_medevac_")]
+    pub medevac: Medevac,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contact: Option<Contact>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remarks: Option<Remarks>,
+}
+
+/// `<_medevac_>` element, holding the 9-line request fields.
+///
+/// Patient counts default to zero and equipment/terrain flags to `false` when absent.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Medevac {
+    #[serde(rename = "@title", skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// `true` for a CASEVAC (non-medical platform), rather than a MEDEVAC.
+    #[serde(rename = "@casevac", default)]
+    pub casevac: bool,
+    /// Line 2: radio frequency.
+    #[serde(rename = "@freq", skip_serializing_if = "Option::is_none")]
+    pub freq: Option<String>,
+
+    // Line 3: number of patients by precedence.
+    #[serde(rename = "@urgent", default)]
+    pub urgent: u32,
+    #[serde(rename = "@urgent_surgical", default)]
+    pub urgent_surgical: u32,
+    #[serde(rename = "@priority", default)]
+    pub priority: u32,
+    #[serde(rename = "@routine", default)]
+    pub routine: u32,
+    #[serde(rename = "@convenience", default)]
+    pub convenience: u32,
+
+    // Line 4: special equipment.
+    #[serde(rename = "@equipment_none", default)]
+    pub equipment_none: bool,
+    #[serde(rename = "@hoist", default)]
+    pub hoist: bool,
+    #[serde(rename = "@extraction_equipment", default)]
+    pub extraction_equipment: bool,
+    #[serde(rename = "@ventilator", default)]
+    pub ventilator: bool,
+    #[serde(rename = "@equipment_other", default)]
+    pub equipment_other: bool,
+    #[serde(rename = "@equipment_detail", skip_serializing_if = "Option::is_none")]
+    pub equipment_detail: Option<String>,
+
+    // Line 5: number of patients by type.
+    #[serde(rename = "@litter", default)]
+    pub litter: u32,
+    #[serde(rename = "@ambulatory", default)]
+    pub ambulatory: u32,
+
+    /// Line 6: security at the pickup site.
+    #[serde(rename = "@security", skip_serializing_if = "Option::is_none")]
+    pub security: Option<Security>,
+
+    /// Line 7: method of marking the pickup site.
+    #[serde(rename = "@hlz_marking", skip_serializing_if = "Option::is_none")]
+    pub hlz_marking: Option<HlzMarking>,
+    #[serde(rename = "@hlz_remarks", skip_serializing_if = "Option::is_none")]
+    pub hlz_remarks: Option<String>,
+
+    // Line 8: number of patients by nationality and status.
+    #[serde(rename = "@us_military", default)]
+    pub us_military: u32,
+    #[serde(rename = "@us_civilian", default)]
+    pub us_civilian: u32,
+    #[serde(rename = "@nonus_military", default)]
+    pub nonus_military: u32,
+    #[serde(rename = "@nonus_civilian", default)]
+    pub nonus_civilian: u32,
+    #[serde(rename = "@epw", default)]
+    pub epw: u32,
+    /// Children, rendered as `F` on line 8 as in ATAK.
+    #[serde(rename = "@child", default)]
+    pub child: u32,
+
+    // Line 9: terrain and obstacles at the pickup site.
+    #[serde(rename = "@terrain_none", default)]
+    pub terrain_none: bool,
+    #[serde(rename = "@terrain_slope", default)]
+    pub terrain_slope: bool,
+    #[serde(rename = "@terrain_slope_dir", skip_serializing_if = "Option::is_none")]
+    pub terrain_slope_dir: Option<String>,
+    #[serde(rename = "@terrain_rough", default)]
+    pub terrain_rough: bool,
+    #[serde(rename = "@terrain_loose", default)]
+    pub terrain_loose: bool,
+    #[serde(rename = "@terrain_other", default)]
+    pub terrain_other: bool,
+    #[serde(
+        rename = "@terrain_other_detail",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub terrain_other_detail: Option<String>,
+    #[serde(rename = "@obstacles", skip_serializing_if = "Option::is_none")]
+    pub obstacles: Option<String>,
+
+    // Zone information.
+    #[serde(rename = "@winds_are_from", skip_serializing_if = "Option::is_none")]
+    pub winds_are_from: Option<String>,
+    #[serde(rename = "@friendlies", skip_serializing_if = "Option::is_none")]
+    pub friendlies: Option<String>,
+    #[serde(rename = "@enemy", skip_serializing_if = "Option::is_none")]
+    pub enemy: Option<String>,
+    #[serde(rename = "@medline_remarks", skip_serializing_if = "Option::is_none")]
+    pub medline_remarks: Option<String>,
+
+    // Zone protection.
+    #[serde(
+        rename = "@zone_prot_selection",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub zone_protection: Option<ZoneProtection>,
+    /// Location of the element protecting the zone, as entered, e.g. an MGRS grid.
+    #[serde(
+        rename = "@zone_protected_coord",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub zone_protected_coord: Option<String>,
+    /// How the protecting element is marked.
+    #[serde(rename = "@zone_prot_marker", skip_serializing_if = "Option::is_none")]
+    pub zone_prot_marker: Option<String>,
+}
+
+/// 9-line line 6: security at the pickup site.
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "u8", into = "u8")]
+pub enum Security {
+    /// N: no enemy troops in area.
+    NoEnemy = 0,
+    /// P: possible enemy troops in area.
+    PossibleEnemy = 1,
+    /// E: enemy troops in area, approach with caution.
+    EnemyInArea = 2,
+    /// X: enemy troops in area, armed escort required.
+    EnemyArmedEscort = 3,
+}
+
+impl Security {
+    /// Brevity letter used in the 9-line.
+    pub fn letter(self) -> char {
+        match self {
+            Security::NoEnemy => 'N',
+            Security::PossibleEnemy => 'P',
+            Security::EnemyInArea => 'E',
+            Security::EnemyArmedEscort => 'X',
+        }
+    }
+}
+
+impl TryFrom<u8> for Security {
+    type Error = Error;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Security::NoEnemy),
+            1 => Ok(Security::PossibleEnemy),
+            2 => Ok(Security::EnemyInArea),
+            3 => Ok(Security::EnemyArmedEscort),
+            _ => Err(Error::BadField(
+                format!("unknown medevac security {}", value).into(),
+            )),
+        }
+    }
+}
+
+impl From<Security> for u8 {
+    fn from(value: Security) -> Self {
+        value as u8
+    }
+}
+
+/// 9-line line 7: method of marking the pickup site.
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "u8", into = "u8")]
+pub enum HlzMarking {
+    Panels = 0,
+    Pyrotechnic = 1,
+    Smoke = 2,
+    None = 3,
+    Other = 4,
+}
+
+impl HlzMarking {
+    /// Brevity letter used in the 9-line.
+    pub fn letter(self) -> char {
+        match self {
+            HlzMarking::Panels => 'A',
+            HlzMarking::Pyrotechnic => 'B',
+            HlzMarking::Smoke => 'C',
+            HlzMarking::None => 'D',
+            HlzMarking::Other => 'E',
+        }
+    }
+}
+
+impl TryFrom<u8> for HlzMarking {
+    type Error = Error;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(HlzMarking::Panels),
+            1 => Ok(HlzMarking::Pyrotechnic),
+            2 => Ok(HlzMarking::Smoke),
+            3 => Ok(HlzMarking::None),
+            4 => Ok(HlzMarking::Other),
+            _ => Err(Error::BadField(
+                format!("unknown medevac HLZ marking {}", value).into(),
+            )),
+        }
+    }
+}
+
+impl From<HlzMarking> for u8 {
+    fn from(value: HlzMarking) -> Self {
+        value as u8
+    }
+}
+
+/// Whether the pickup zone is protected by friendly forces.
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "u8", into = "u8")]
+pub enum ZoneProtection {
+    Unprotected = 0,
+    Protected = 1,
+}
+
+impl TryFrom<u8> for ZoneProtection {
+    type Error = Error;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(ZoneProtection::Unprotected),
+            1 => Ok(ZoneProtection::Protected),
+            _ => Err(Error::BadField(
+                format!("unknown medevac zone protection {}", value).into(),
+            )),
+        }
+    }
+}
+
+impl From<ZoneProtection> for u8 {
+    fn from(value: ZoneProtection) -> Self {
+        value as u8
+    }
+}
+
+/// Join non-zero counts as e.g. `2A, 1C`.
+fn counts(items: &[(u32, char)]) -> String {
+    let parts: Vec<String> = items
+        .iter()
+        .filter(|(n, _)| *n > 0)
+        .map(|(n, c)| format!("{}{}", n, c))
+        .collect();
+    parts.join(", ")
+}
+
+/// Join the letters whose flag is set, e.g. `B, C`.
+fn flags(items: &[(bool, char)]) -> String {
+    let parts: Vec<String> = items
+        .iter()
+        .filter(|(set, _)| *set)
+        .map(|(_, c)| c.to_string())
+        .collect();
+    parts.join(", ")
+}
+
+impl Medevac {
+    /// Total number of patients, by precedence.
+    pub fn patient_count(&self) -> u32 {
+        self.urgent + self.urgent_surgical + self.priority + self.routine + self.convenience
+    }
+}
+
+impl MedevacDetail {
+    /// Render the request as a textual 9-line, with line 1 being the pickup site `location` as
+    /// a 1 m MGRS reference (or latitude, longitude in the polar regions MGRS doesn't cover).
+    ///
+    /// Lines use the standard brevity codes, e.g. `Line 3: 2A, 1C` for two urgent and one
+    /// priority patient. Lines with no information are left blank after the colon.
+    pub fn nine_line(&self, location: &Point) -> String {
+        let m = &self.medevac;
+        let callsign = self.contact.as_ref().map(|c| c.callsign.as_str());
+        let line2 = [m.freq.as_deref(), callsign]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" ");
+        let mut line4 = flags(&[
+            (m.equipment_none, 'A'),
+            (m.hoist, 'B'),
+            (m.extraction_equipment, 'C'),
+            (m.ventilator, 'D'),
+            (m.equipment_other, 'E'),
+        ]);
+        if let Some(detail) = m.equipment_detail.as_deref().filter(|_| m.equipment_other) {
+            line4 = format!("{} ({})", line4, detail);
+        }
+        let mut line7 = m
+            .hlz_marking
+            .map(|h| h.letter().to_string())
+            .unwrap_or_default();
+        if let Some(remarks) = &m.hlz_remarks {
+            line7 = format!("{} ({})", line7, remarks);
+        }
+        let mut terrain = vec![];
+        if m.terrain_slope {
+            match &m.terrain_slope_dir {
+                Some(dir) => terrain.push(format!("slope {}", dir)),
+                None => terrain.push("slope".to_string()),
+            }
+        }
+        if m.terrain_rough {
+            terrain.push("rough".to_string());
+        }
+        if m.terrain_loose {
+            terrain.push("loose".to_string());
+        }
+        if m.terrain_other {
+            terrain.push(
+                m.terrain_other_detail
+                    .clone()
+                    .unwrap_or("other".to_string()),
+            );
+        }
+        if terrain.is_empty() && m.terrain_none {
+            terrain.push("none".to_string());
+        }
+        if let Some(obstacles) = &m.obstacles {
+            terrain.push(format!("obstacles: {}", obstacles));
+        }
+
+        let lines = [
+            coords::to_mgrs(location.lat, location.lon, 5)
+                .unwrap_or_else(|_| format!("{:.5}, {:.5}", location.lat, location.lon)),
+            line2,
+            counts(&[
+                (m.urgent, 'A'),
+                (m.urgent_surgical, 'B'),
+                (m.priority, 'C'),
+                (m.routine, 'D'),
+                (m.convenience, 'E'),
+            ]),
+            line4,
+            counts(&[(m.litter, 'L'), (m.ambulatory, 'A')]),
+            m.security
+                .map(|s| s.letter().to_string())
+                .unwrap_or_default(),
+            line7,
+            counts(&[
+                (m.us_military, 'A'),
+                (m.us_civilian, 'B'),
+                (m.nonus_military, 'C'),
+                (m.nonus_civilian, 'D'),
+                (m.epw, 'E'),
+                (m.child, 'F'),
+            ]),
+            terrain.join(", "),
+        ];
+        let mut text = String::new();
+        if let Some(title) = &m.title {
+            text.push_str(&format!("{}\n", title));
+        }
+        for (i, line) in lines.iter().enumerate() {
+            text.push_str(&format!("Line {}: {}\n", i + 1, line));
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::base::Cot;
+
+    use super::*;
+
+    #[test]
+    fn test_medevac_example() {
+        let xml_path = format!(
+            "{}/src/tak/examples/medevac.cot",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let xml_text = std::fs::read_to_string(xml_path).unwrap();
+        let cot: Cot<MedevacDetail> = quick_xml::de::from_str(&xml_text).unwrap();
+        let m = &cot.detail.medevac;
+        assert_eq!(m.title.as_deref(), Some("MED.161950"));
+        assert_eq!(m.patient_count(), 3);
+        assert_eq!(m.security, Some(Security::PossibleEnemy));
+        assert_eq!(m.hlz_marking, Some(HlzMarking::Smoke));
+        assert!(m.hoist);
+        assert_eq!(m.zone_protection, Some(ZoneProtection::Protected));
+        assert_eq!(m.zone_prot_marker.as_deref(), Some("VS-17 panel"));
+
+        let text = cot.detail.nine_line(&cot.point);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "MED.161950");
+        assert_eq!(lines[1], "Line 1: 18S UJ 20937 02892");
+        assert_eq!(lines[2], "Line 2: 38.90 DUSTOFF 6");
+        assert_eq!(lines[3], "Line 3: 2A, 1C");
+        assert_eq!(lines[4], "Line 4: B");
+        assert_eq!(lines[5], "Line 5: 1L, 2A");
+        assert_eq!(lines[6], "Line 6: P");
+        assert_eq!(lines[7], "Line 7: C (green smoke)");
+        assert_eq!(lines[8], "Line 8: 3A");
+        assert_eq!(lines[9], "Line 9: slope N, obstacles: power lines");
+
+        let xml = quick_xml::se::to_string(&cot).unwrap();
+        assert!(
+            xml.contains(r#"zone_prot_selection="1" zone_protected_coord="18S UJ 23371 06519""#)
+        );
+        let cot1: Cot<MedevacDetail> = quick_xml::de::from_str(&xml).unwrap();
+        assert_eq!(cot.detail, cot1.detail);
+
+        let mut detail = cot.detail.clone();
+        detail.medevac.child = 1;
+        let text = detail.nine_line(&cot.point);
+        assert!(text.contains("Line 8: 3A, 1F\n"));
+        // No MGRS in the polar regions.
+        let text = detail.nine_line(&Point::from_lat_lon(85.0, 10.0));
+        assert!(text.contains("Line 1: 85.00000, 10.00000\n"));
+    }
+}
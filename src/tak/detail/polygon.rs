@@ -0,0 +1,177 @@
+use serde::{Deserialize, Serialize};
+
+use crate::base::Point;
+
+use super::{Contact, Empty, LinkPoint, PrecisionLocation, Remarks, TakColor, ValueElement};
+
+/// `<detail>` section for freehand polygons and polylines (`u-d-f`).
+///
+/// Vertices are the ordered `<link point='lat,lon'/>` elements. A polygon is closed by repeating
+/// its first vertex at the end; open polylines normally have no fill color.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct TakPolygonDetail {
+    #[serde(rename = "link", default)]
+    pub links: Vec<VertexLink>,
+    #[serde(rename = "strokeColor", skip_serializing_if = "Option::is_none")]
+    pub stroke_color: Option<ValueElement<TakColor>>,
+    #[serde(rename = "strokeWeight", skip_serializing_if = "Option::is_none")]
+    pub stroke_weight: Option<ValueElement<f32>>,
+    #[serde(rename = "fillColor", skip_serializing_if = "Option::is_none")]
+    pub fill_color: Option<ValueElement<TakColor>>,
+    pub contact: Contact,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remarks: Option<Remarks>,
+    /// `<archive/>`: receivers persist the shape.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archive: Option<Empty>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub labels_on: Option<ValueElement<bool>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<ValueElement<TakColor>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub precisionlocation: Option<PrecisionLocation>,
+}
+
+/// `<detail>` section for rectangles (`u-d-r`).
+///
+/// The four corners are stored as `<link point='lat,lon'/>` elements, in order around the
+/// rectangle (ATAK starts at the north-west corner and goes clockwise). The rectangle is not
+/// closed by repeating the first corner.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct TakRectangleDetail {
+    #[serde(rename = "link", default)]
+    pub links: Vec<VertexLink>,
+    #[serde(rename = "strokeColor", skip_serializing_if = "Option::is_none")]
+    pub stroke_color: Option<ValueElement<TakColor>>,
+    #[serde(rename = "strokeWeight", skip_serializing_if = "Option::is_none")]
+    pub stroke_weight: Option<ValueElement<f32>>,
+    #[serde(rename = "fillColor", skip_serializing_if = "Option::is_none")]
+    pub fill_color: Option<ValueElement<TakColor>>,
+    pub contact: Contact,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tog: Option<Tog>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remarks: Option<Remarks>,
+    /// `<archive/>`: receivers persist the shape.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archive: Option<Empty>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub labels_on: Option<ValueElement<bool>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub precisionlocation: Option<PrecisionLocation>,
+}
+
+/// A shape vertex: `<link point='lat,lon'/>`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct VertexLink {
+    #[serde(rename = "@point")]
+    pub point: LinkPoint,
+}
+
+impl From<LinkPoint> for VertexLink {
+    fn from(point: LinkPoint) -> Self {
+        Self { point }
+    }
+}
+
+/// `<tog>` element sent by ATAK with rectangles.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Tog {
+    #[serde(rename = "@enabled")]
+    pub enabled: u8,
+}
+
+impl TakPolygonDetail {
+    /// Vertices of the shape, in order.
+    pub fn vertices(&self) -> Vec<LinkPoint> {
+        self.links.iter().map(|l| l.point).collect()
+    }
+
+    /// Whether the shape is a closed polygon, i.e. its last vertex repeats the first.
+    pub fn is_closed(&self) -> bool {
+        self.links.len() > 2 && self.links.first() == self.links.last()
+    }
+}
+
+impl TakRectangleDetail {
+    /// The four corners of the rectangle, in order, or `None` if there are not exactly four.
+    pub fn corners(&self) -> Option<[LinkPoint; 4]> {
+        match self.links.as_slice() {
+            [a, b, c, d] => Some([a.point, b.point, c.point, d.point]),
+            _ => None,
+        }
+    }
+
+    /// Center of the rectangle, i.e. the midpoint of its diagonals.
+    pub fn center(&self) -> Option<LinkPoint> {
+        let [a, _, c, _] = self.corners()?;
+        Some(LinkPoint::new((a.lat + c.lat) / 2.0, (a.lon + c.lon) / 2.0))
+    }
+
+    /// Lengths in meters of the first side (between the first two corners) and the second side.
+    pub fn side_lengths(&self) -> Option<(f64, f64)> {
+        let [a, b, c, _] = self.corners()?.map(|p| p.to_point());
+        Some((a.distance_to(&b), b.distance_to(&c)))
+    }
+}
+
+/// Corners of an axis-aligned rectangle spanned by two opposite corners, in ATAK order: north-west,
+/// north-east, south-east, south-west.
+pub fn rectangle_corners(corner: &Point, opposite: &Point) -> [LinkPoint; 4] {
+    let (north, south) = (corner.lat.max(opposite.lat), corner.lat.min(opposite.lat));
+    let (east, west) = (corner.lon.max(opposite.lon), corner.lon.min(opposite.lon));
+    [
+        LinkPoint::new(north, west),
+        LinkPoint::new(north, east),
+        LinkPoint::new(south, east),
+        LinkPoint::new(south, west),
+    ]
+}
+
+#[cfg(test)]
+mod test {
+    use crate::base::Cot;
+
+    use super::*;
+
+    fn read_example(name: &str) -> String {
+        let xml_path = format!("{}/src/tak/examples/{}", env!("CARGO_MANIFEST_DIR"), name);
+        std::fs::read_to_string(xml_path).unwrap()
+    }
+
+    #[test]
+    fn test_polygon_roundtrip() {
+        let cot: Cot<TakPolygonDetail> =
+            quick_xml::de::from_str(&read_example("shape-free.cot")).unwrap();
+        assert_eq!(cot.detail.links.len(), 7);
+        assert!(cot.detail.is_closed());
+        assert_eq!(cot.detail.contact.callsign, "Shape 1");
+        assert_eq!(cot.detail.archive, Some(Empty {}));
+
+        let xml = quick_xml::se::to_string(&cot).unwrap();
+        assert!(xml.contains("<remarks/><archive/><labels_on"), "{}", xml);
+        let cot1: Cot<TakPolygonDetail> = quick_xml::de::from_str(&xml).unwrap();
+        assert_eq!(cot, cot1);
+    }
+
+    #[test]
+    fn test_rectangle_roundtrip() {
+        let cot: Cot<TakRectangleDetail> =
+            quick_xml::de::from_str(&read_example("shape-rect.cot")).unwrap();
+        let [nw, ne, se, sw] = cot.detail.corners().unwrap();
+        assert!(nw.lat > sw.lat && nw.lon < ne.lon);
+        assert!(se.lat < ne.lat && se.lon > sw.lon);
+        let center = cot.detail.center().unwrap();
+        assert!((center.lat - cot.point.lat).abs() < 1e-4);
+        assert!((center.lon - cot.point.lon).abs() < 1e-4);
+        let (width, height) = cot.detail.side_lengths().unwrap();
+        assert!(width > 100.0 && height > 200.0, "{} x {}", width, height);
+        assert_eq!(cot.detail.tog, Some(Tog { enabled: 0 }));
+        assert_eq!(cot.detail.archive, Some(Empty {}));
+
+        let xml = quick_xml::se::to_string(&cot).unwrap();
+        assert!(xml.contains("<remarks/><archive/><labels_on"), "{}", xml);
+        let cot1: Cot<TakRectangleDetail> = quick_xml::de::from_str(&xml).unwrap();
+        assert_eq!(cot, cot1);
+    }
+}
@@ -0,0 +1,396 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::base::{Point, UNKNOWN_VALUE};
+use crate::Error;
+
+use super::{Contact, Empty, Remarks, TakColor, ValueElement};
+
+/// `<detail>` section for a range & bearing line (`u-rb-a`), which starts at the event's `point`.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct RangeBearingDetail {
+    /// Range, in `range_units`.
+    pub range: ValueElement<f64>,
+    /// Bearing, in `bearing_units` relative to `north_ref`.
+    pub bearing: ValueElement<f64>,
+    /// Inclination in degrees above the horizontal.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inclination: Option<ValueElement<f64>>,
+    #[serde(rename = "rangeUnits")]
+    pub range_units: ValueElement<RangeUnits>,
+    #[serde(rename = "bearingUnits")]
+    pub bearing_units: ValueElement<BearingUnits>,
+    #[serde(rename = "northRef")]
+    pub north_ref: ValueElement<NorthRef>,
+    #[serde(rename = "strokeColor", skip_serializing_if = "Option::is_none")]
+    pub stroke_color: Option<ValueElement<TakColor>>,
+    #[serde(rename = "strokeWeight", skip_serializing_if = "Option::is_none")]
+    pub stroke_weight: Option<ValueElement<f32>>,
+    pub contact: Contact,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remarks: Option<Remarks>,
+    /// `<archive/>`: receivers persist the line.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archive: Option<Empty>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub labels_on: Option<ValueElement<bool>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<ValueElement<TakColor>>,
+}
+
+/// Distance units, encoded as ATAK's `Span` values.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "u8", into = "u8")]
+pub enum RangeUnits {
+    Kilometers = 0,
+    #[default]
+    Meters = 1,
+    Miles = 2,
+    Yards = 3,
+    Feet = 4,
+    NauticalMiles = 5,
+}
+
+impl RangeUnits {
+    /// Length of one unit in meters.
+    pub fn meters_per_unit(self) -> f64 {
+        match self {
+            RangeUnits::Kilometers => 1000.0,
+            RangeUnits::Meters => 1.0,
+            RangeUnits::Miles => 1609.344,
+            RangeUnits::Yards => 0.9144,
+            RangeUnits::Feet => 0.3048,
+            RangeUnits::NauticalMiles => 1852.0,
+        }
+    }
+
+    pub fn to_meters(self, value: f64) -> f64 {
+        value * self.meters_per_unit()
+    }
+
+    pub fn from_meters(self, meters: f64) -> f64 {
+        meters / self.meters_per_unit()
+    }
+
+    /// Unit abbreviation, e.g. `nm`.
+    pub fn abbreviation(self) -> &'static str {
+        match self {
+            RangeUnits::Kilometers => "km",
+            RangeUnits::Meters => "m",
+            RangeUnits::Miles => "mi",
+            RangeUnits::Yards => "yd",
+            RangeUnits::Feet => "ft",
+            RangeUnits::NauticalMiles => "nm",
+        }
+    }
+
+    /// Parse a unit abbreviation, ignoring case.
+    pub fn from_abbreviation(abbreviation: &str) -> Option<Self> {
+        match abbreviation.to_ascii_lowercase().as_str() {
+            "km" => Some(RangeUnits::Kilometers),
+            "m" => Some(RangeUnits::Meters),
+            "mi" => Some(RangeUnits::Miles),
+            "yd" => Some(RangeUnits::Yards),
+            "ft" => Some(RangeUnits::Feet),
+            "nm" => Some(RangeUnits::NauticalMiles),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<u8> for RangeUnits {
+    type Error = Error;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(RangeUnits::Kilometers),
+            1 => Ok(RangeUnits::Meters),
+            2 => Ok(RangeUnits::Miles),
+            3 => Ok(RangeUnits::Yards),
+            4 => Ok(RangeUnits::Feet),
+            5 => Ok(RangeUnits::NauticalMiles),
+            _ => Err(Error::BadField(
+                format!("unknown range units {}", value).into(),
+            )),
+        }
+    }
+}
+
+impl From<RangeUnits> for u8 {
+    fn from(units: RangeUnits) -> Self {
+        units as u8
+    }
+}
+
+/// Angle units, encoded as ATAK's `Angle` values.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "u8", into = "u8")]
+pub enum BearingUnits {
+    #[default]
+    Degrees = 0,
+    /// NATO mils: 6400 in a full circle.
+    Mils = 1,
+    Radians = 2,
+}
+
+/// Number of NATO mils in a full circle.
+pub const MILS_PER_CIRCLE: f64 = 6400.0;
+
+impl BearingUnits {
+    pub fn to_degrees(self, value: f64) -> f64 {
+        match self {
+            BearingUnits::Degrees => value,
+            BearingUnits::Mils => value * 360.0 / MILS_PER_CIRCLE,
+            BearingUnits::Radians => value.to_degrees(),
+        }
+    }
+
+    pub fn from_degrees(self, degrees: f64) -> f64 {
+        match self {
+            BearingUnits::Degrees => degrees,
+            BearingUnits::Mils => degrees * MILS_PER_CIRCLE / 360.0,
+            BearingUnits::Radians => degrees.to_radians(),
+        }
+    }
+}
+
+impl TryFrom<u8> for BearingUnits {
+    type Error = Error;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(BearingUnits::Degrees),
+            1 => Ok(BearingUnits::Mils),
+            2 => Ok(BearingUnits::Radians),
+            _ => Err(Error::BadField(
+                format!("unknown bearing units {}", value).into(),
+            )),
+        }
+    }
+}
+
+impl From<BearingUnits> for u8 {
+    fn from(units: BearingUnits) -> Self {
+        units as u8
+    }
+}
+
+/// North reference for bearings, encoded as ATAK's `NorthReference` values.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "u8", into = "u8")]
+pub enum NorthRef {
+    #[default]
+    True = 0,
+    Magnetic = 1,
+    Grid = 2,
+}
+
+impl NorthRef {
+    /// Convert a bearing in degrees relative to this north reference to degrees from true north.
+    ///
+    /// `declination` is the magnetic declination (positive east) and `convergence` the grid
+    /// convergence (angle from true north to grid north, positive east), both in degrees.
+    pub fn to_true(self, bearing: f64, declination: f64, convergence: f64) -> f64 {
+        let b = match self {
+            NorthRef::True => bearing,
+            NorthRef::Magnetic => bearing + declination,
+            NorthRef::Grid => bearing + convergence,
+        };
+        b.rem_euclid(360.0)
+    }
+
+    /// Convert a bearing in degrees from true north to this north reference. See
+    /// [`NorthRef::to_true()`].
+    pub fn from_true(self, bearing: f64, declination: f64, convergence: f64) -> f64 {
+        let b = match self {
+            NorthRef::True => bearing,
+            NorthRef::Magnetic => bearing - declination,
+            NorthRef::Grid => bearing - convergence,
+        };
+        b.rem_euclid(360.0)
+    }
+
+    /// Convert a bearing in degrees relative to this north reference at `point` to degrees from
+    /// true north, using the World Magnetic Model declination at `time` and the UTM/UPS grid
+    /// convergence. See [`Point::declination()`] and [`Point::grid_convergence()`].
+    pub fn to_true_at(
+        self,
+        bearing: f64,
+        point: &Point,
+        time: &DateTime<Utc>,
+    ) -> Result<f64, Error> {
+        let (declination, convergence) = Self::corrections_at(point, time)?;
+        Ok(self.to_true(bearing, declination, convergence))
+    }
+
+    /// Convert a bearing in degrees from true north at `point` to this north reference. See
+    /// [`NorthRef::to_true_at()`].
+    pub fn from_true_at(
+        self,
+        bearing: f64,
+        point: &Point,
+        time: &DateTime<Utc>,
+    ) -> Result<f64, Error> {
+        let (declination, convergence) = Self::corrections_at(point, time)?;
+        Ok(self.from_true(bearing, declination, convergence))
+    }
+
+    /// Declination and grid convergence at `point`.
+    fn corrections_at(point: &Point, time: &DateTime<Utc>) -> Result<(f64, f64), Error> {
+        Ok((point.declination(time), point.grid_convergence()?))
+    }
+}
+
+impl TryFrom<u8> for NorthRef {
+    type Error = Error;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(NorthRef::True),
+            1 => Ok(NorthRef::Magnetic),
+            2 => Ok(NorthRef::Grid),
+            _ => Err(Error::BadField(
+                format!("unknown north reference {}", value).into(),
+            )),
+        }
+    }
+}
+
+impl From<NorthRef> for u8 {
+    fn from(north_ref: NorthRef) -> Self {
+        north_ref as u8
+    }
+}
+
+impl RangeBearingDetail {
+    /// Create a line with the given range and bearing, and no styling.
+    pub fn new(
+        range: f64,
+        range_units: RangeUnits,
+        bearing: f64,
+        bearing_units: BearingUnits,
+        north_ref: NorthRef,
+    ) -> Self {
+        Self {
+            range: range.into(),
+            bearing: bearing.into(),
+            inclination: Some(0.0.into()),
+            range_units: range_units.into(),
+            bearing_units: bearing_units.into(),
+            north_ref: north_ref.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Range (slant range, if there is an inclination) in meters.
+    pub fn range_m(&self) -> f64 {
+        self.range_units.value.to_meters(self.range.value)
+    }
+
+    /// Bearing in degrees, relative to `north_ref`.
+    pub fn bearing_deg(&self) -> f64 {
+        self.bearing_units.value.to_degrees(self.bearing.value)
+    }
+
+    /// Bearing in degrees from true north. See [`NorthRef::to_true()`] for the correction
+    /// parameters; they are only used for magnetic and grid bearings respectively.
+    pub fn bearing_true_deg(&self, declination: f64, convergence: f64) -> f64 {
+        self.north_ref
+            .value
+            .to_true(self.bearing_deg(), declination, convergence)
+    }
+
+    /// Compute the end point of the line starting at `start`, which is normally the event's
+    /// `point`. The horizontal range is reduced by the inclination, and the end altitude adjusted
+    /// if the start altitude is known.
+    pub fn end_point(&self, start: &Point, declination: f64, convergence: f64) -> Point {
+        let inclination = self.inclination.as_ref().map_or(0.0, |i| i.value);
+        let range = self.range_m();
+        let horizontal = range * inclination.to_radians().cos();
+        let mut end =
+            start.destination(self.bearing_true_deg(declination, convergence), horizontal);
+        if start.hae != UNKNOWN_VALUE {
+            end.hae = start.hae + (range * inclination.to_radians().sin()) as f32;
+        }
+        end
+    }
+
+    /// Compute the end point of the line starting at `start`, with the magnetic declination at
+    /// `time` and grid convergence at `start`. See [`NorthRef::to_true_at()`].
+    pub fn end_point_at(&self, start: &Point, time: &DateTime<Utc>) -> Result<Point, Error> {
+        let declination = start.declination(time);
+        let convergence = start.grid_convergence()?;
+        Ok(self.end_point(start, declination, convergence))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::base::Cot;
+
+    use super::*;
+
+    #[test]
+    fn test_range_bearing_example() {
+        let xml_path = format!(
+            "{}/src/tak/examples/range-bearing-line.cot",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let xml_text = std::fs::read_to_string(xml_path).unwrap();
+        let cot: Cot<RangeBearingDetail> = quick_xml::de::from_str(&xml_text).unwrap();
+        let rb = &cot.detail;
+        assert_eq!(rb.range_units.value, RangeUnits::Meters);
+        assert_eq!(rb.bearing_units.value, BearingUnits::Degrees);
+        assert_eq!(rb.north_ref.value, NorthRef::Magnetic);
+        assert!((rb.range_m() - 886.144457943895).abs() < 1e-9);
+        assert_eq!(rb.archive, Some(Empty {}));
+        // 10 degrees west declination
+        assert!((rb.bearing_true_deg(-10.0, 0.0) - 35.59655671674022).abs() < 1e-9);
+
+        let end = rb.end_point(&cot.point, -10.0, 0.0);
+        assert!((cot.point.distance_to(&end) - rb.range_m()).abs() < 1e-6);
+        assert_eq!(end.hae, cot.point.hae);
+        let end_at = rb.end_point_at(&cot.point, &cot.time).unwrap();
+        let true_bearing = NorthRef::Magnetic
+            .to_true_at(rb.bearing_deg(), &cot.point, &cot.time)
+            .unwrap();
+        assert!((cot.point.bearing_to(&end_at) - true_bearing).abs() < 1e-6);
+
+        let xml = quick_xml::se::to_string(&cot).unwrap();
+        assert!(xml.contains(r#"<northRef value="1"/>"#));
+        assert!(xml.contains("<remarks/><archive/><labels_on"), "{}", xml);
+        let cot1: Cot<RangeBearingDetail> = quick_xml::de::from_str(&xml).unwrap();
+        assert_eq!(cot, cot1);
+    }
+
+    #[test]
+    fn test_units() {
+        let rb = RangeBearingDetail::new(
+            2.0,
+            RangeUnits::NauticalMiles,
+            1600.0,
+            BearingUnits::Mils,
+            NorthRef::Grid,
+        );
+        assert_eq!(rb.range_m(), 3704.0);
+        assert_eq!(rb.bearing_deg(), 90.0);
+        assert_eq!(rb.bearing_true_deg(5.0, -1.5), 88.5);
+        assert_eq!(NorthRef::Magnetic.from_true(5.0, 10.0, 0.0), 355.0);
+
+        // WMM2025 declination and UTM zone 31 convergence in Paris
+        let time = "2026-01-01T00:00:00Z".parse().unwrap();
+        let paris = Point::from_lat_lon(48.8582, 2.2945);
+        let declination = paris.declination(&time);
+        assert!(declination > 1.0 && declination < 2.0, "{}", declination);
+        let magnetic = NorthRef::Magnetic
+            .from_true_at(90.0, &paris, &time)
+            .unwrap();
+        assert!((magnetic - (90.0 - declination)).abs() < 1e-9);
+        let grid = NorthRef::Grid.from_true_at(90.0, &paris, &time).unwrap();
+        assert!((grid - 90.5313).abs() < 1e-3, "{}", grid);
+        let back = NorthRef::Grid.to_true_at(grid, &paris, &time).unwrap();
+        assert!((back - 90.0).abs() < 1e-9);
+        assert_eq!(BearingUnits::Mils.from_degrees(180.0), 3200.0);
+        assert!(RangeUnits::try_from(6).is_err());
+    }
+}
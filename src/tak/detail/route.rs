@@ -0,0 +1,278 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Contact, Empty, LinkPoint, Remarks, TakColor, ValueElement};
+
+/// `<detail>` section for a Route (`b-m-r`) message.
+///
+/// The route's points are the ordered `<link>` elements, followed by a `<link_attr>` element with
+/// the route properties.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct TakRouteDetail {
+    #[serde(rename = "link", default)]
+    pub links: Vec<RouteLink>,
+    pub link_attr: LinkAttr,
+    #[serde(rename = "strokeColor", skip_serializing_if = "Option::is_none")]
+    pub stroke_color: Option<ValueElement<TakColor>>,
+    #[serde(rename = "strokeWeight", skip_serializing_if = "Option::is_none")]
+    pub stroke_weight: Option<ValueElement<f32>>,
+    #[serde(rename = "__routeinfo", default)]
+    pub route_info: RouteInfo,
+    pub contact: Contact,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remarks: Option<Remarks>,
+    /// `<archive/>`: receivers persist the route.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archive: Option<Empty>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub labels_on: Option<ValueElement<bool>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<ValueElement<TakColor>>,
+}
+
+/// One point along a route, i.e. a `<link>` element in a route's `<detail>`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct RouteLink {
+    #[serde(rename = "@uid")]
+    pub uid: String,
+    #[serde(rename = "@callsign", default)]
+    pub callsign: String,
+    #[serde(rename = "@type")]
+    pub point_type: RoutePointType,
+    #[serde(rename = "@point")]
+    pub point: LinkPoint,
+    #[serde(rename = "@remarks", default)]
+    pub remarks: String,
+    #[serde(rename = "@relation", default = "default_relation")]
+    pub relation: String,
+}
+
+fn default_relation() -> String {
+    "c".to_string()
+}
+
+impl RouteLink {
+    pub fn new(point_type: RoutePointType, callsign: &str, point: LinkPoint) -> Self {
+        Self {
+            uid: uuid::Uuid::new_v4().to_string(),
+            callsign: callsign.to_string(),
+            point_type,
+            point,
+            remarks: String::new(),
+            relation: default_relation(),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum RoutePointType {
+    /// Named checkpoint shown on the map (`b-m-p-w`).
+    #[serde(rename = "b-m-p-w")]
+    Waypoint,
+    /// Unnamed control point which only shapes the route (`b-m-p-c`).
+    #[serde(rename = "b-m-p-c")]
+    ControlPoint,
+}
+
+/// Route properties, from the `<link_attr>` element.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct LinkAttr {
+    #[serde(rename = "@planningmethod")]
+    pub planning_method: RoutePlanningMethod,
+    #[serde(rename = "@color", skip_serializing_if = "Option::is_none")]
+    pub color: Option<TakColor>,
+    #[serde(rename = "@method")]
+    pub method: RouteMethod,
+    #[serde(rename = "@prefix")]
+    pub prefix: String,
+    /// Legacy route type, e.g. `Vehicle` or `Foot`.
+    #[serde(rename = "@type", skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    #[serde(rename = "@stroke", skip_serializing_if = "Option::is_none")]
+    pub stroke: Option<u32>,
+    #[serde(rename = "@direction")]
+    pub direction: RouteDirection,
+    #[serde(rename = "@routetype")]
+    pub route_type: RouteType,
+    #[serde(rename = "@order")]
+    pub order: RouteOrder,
+}
+
+impl Default for LinkAttr {
+    fn default() -> Self {
+        Self {
+            planning_method: RoutePlanningMethod::Infil,
+            color: Some(TakColor::WHITE),
+            method: RouteMethod::Driving,
+            prefix: "CP".to_string(),
+            kind: Some("Vehicle".to_string()),
+            stroke: Some(3),
+            direction: RouteDirection::Infil,
+            route_type: RouteType::Primary,
+            order: RouteOrder::Ascending,
+        }
+    }
+}
+
+/// How the route is travelled.
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum RouteMethod {
+    Driving,
+    Walking,
+    Flying,
+    Swimming,
+    Watercraft,
+}
+
+/// Whether the route was planned for going in to or out of an objective.
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum RoutePlanningMethod {
+    Infil,
+    Exfil,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum RouteDirection {
+    Infil,
+    Exfil,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum RouteType {
+    Primary,
+    Secondary,
+}
+
+/// Order in which checkpoints are numbered.
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum RouteOrder {
+    #[serde(rename = "Ascending Check Points")]
+    Ascending,
+    #[serde(rename = "Descending Check Points")]
+    Descending,
+}
+
+/// `<__routeinfo>` element.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct RouteInfo {
+    #[serde(rename = "__navcues", default)]
+    pub nav_cues: NavCues,
+}
+
+/// `<__navcues>` element: navigation cues announced when approaching route points.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct NavCues {
+    #[serde(rename = "__cue", default)]
+    pub cues: Vec<NavCue>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct NavCue {
+    /// uid of the route point this cue belongs to.
+    #[serde(rename = "@id")]
+    pub id: String,
+    #[serde(rename = "@voice", default)]
+    pub voice: String,
+    #[serde(rename = "@text", default)]
+    pub text: String,
+    #[serde(rename = "trigger", default)]
+    pub triggers: Vec<NavCueTrigger>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct NavCueTrigger {
+    #[serde(rename = "@mode")]
+    pub mode: String,
+    #[serde(rename = "@value")]
+    pub value: String,
+}
+
+/// A section of a route between two consecutive waypoints (or the route's end points).
+#[derive(Clone, Debug, PartialEq)]
+pub struct RouteLeg<'a> {
+    pub from: &'a RouteLink,
+    pub to: &'a RouteLink,
+    /// Distance along the route in meters, including any control points in between.
+    pub distance: f64,
+}
+
+impl TakRouteDetail {
+    /// Total length of the route in meters.
+    pub fn total_length(&self) -> f64 {
+        self.links
+            .windows(2)
+            .map(|w| w[0].point.to_point().distance_to(&w[1].point.to_point()))
+            .sum()
+    }
+
+    /// Split the route into legs between waypoints. The first and last points of the route are
+    /// always treated as leg end points.
+    pub fn legs(&self) -> Vec<RouteLeg<'_>> {
+        let mut legs = vec![];
+        let Some(mut from) = self.links.first() else {
+            return legs;
+        };
+        let mut distance = 0.0;
+        let last = self.links.len() - 1;
+        for (i, w) in self.links.windows(2).enumerate() {
+            distance += w[0].point.to_point().distance_to(&w[1].point.to_point());
+            let to = &w[1];
+            if to.point_type == RoutePointType::Waypoint || i + 1 == last {
+                legs.push(RouteLeg { from, to, distance });
+                from = to;
+                distance = 0.0;
+            }
+        }
+        legs
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::base::Cot;
+
+    use super::*;
+
+    fn route_example() -> Cot<TakRouteDetail> {
+        let xml_path = format!("{}/src/tak/examples/route.cot", env!("CARGO_MANIFEST_DIR"));
+        let xml_text = std::fs::read_to_string(xml_path).unwrap();
+        quick_xml::de::from_str(&xml_text).unwrap()
+    }
+
+    #[test]
+    fn test_deserialize_route() {
+        let route = route_example();
+        let detail = &route.detail;
+        assert_eq!(detail.links.len(), 13);
+        assert_eq!(detail.links[0].callsign, "Route 1 SP");
+        assert_eq!(detail.links[0].point_type, RoutePointType::Waypoint);
+        assert_eq!(detail.links[1].point_type, RoutePointType::ControlPoint);
+        assert_eq!(detail.link_attr.method, RouteMethod::Driving);
+        assert_eq!(detail.link_attr.order, RouteOrder::Ascending);
+        assert_eq!(detail.contact.callsign, "Route 1");
+        assert_eq!(detail.link_attr.planning_method, RoutePlanningMethod::Infil);
+        assert_eq!(detail.archive, Some(Empty {}));
+
+        let xml = quick_xml::se::to_string(&route).unwrap();
+        assert!(xml.contains("<remarks/><archive/><labels_on"), "{}", xml);
+        let route1: Cot<TakRouteDetail> = quick_xml::de::from_str(&xml).unwrap();
+        assert_eq!(route, route1);
+    }
+
+    #[test]
+    fn test_route_legs() {
+        let route = route_example();
+        let legs = route.detail.legs();
+        let names: Vec<_> = legs
+            .iter()
+            .map(|l| (l.from.callsign.as_str(), l.to.callsign.as_str()))
+            .collect();
+        assert_eq!(
+            names,
+            [("Route 1 SP", "CP1"), ("CP1", "CP2"), ("CP2", "VDO")]
+        );
+        let leg_total: f64 = legs.iter().map(|l| l.distance).sum();
+        let total = route.detail.total_length();
+        assert!((leg_total - total).abs() < 1e-6);
+        assert!(total > 5000.0 && total < 6000.0, "total {}", total);
+    }
+}
@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Contact, PrecisionLocation, Status, TakColor};
+
+/// `<detail>` section for a self situational awareness (SA) message, i.e. the position report
+/// ("friendly PLI") which puts a TAK user on the map as a team member.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct TakSaDetail {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub takv: Option<Takv>,
+    pub contact: Contact,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uid: Option<Uid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub precisionlocation: Option<PrecisionLocation>,
+    #[serde(rename = "__group", skip_serializing_if = "Option::is_none")]
+    pub group: Option<Group>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<Status>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub track: Option<Track>,
+}
+
+/// `<takv>` element: the sending device and TAK software.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Takv {
+    #[serde(rename = "@device", skip_serializing_if = "Option::is_none")]
+    pub device: Option<String>,
+    #[serde(rename = "@platform", skip_serializing_if = "Option::is_none")]
+    pub platform: Option<String>,
+    #[serde(rename = "@os", skip_serializing_if = "Option::is_none")]
+    pub os: Option<String>,
+    #[serde(rename = "@version", skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+/// `<uid>` element.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Uid {
+    /// Device callsign.
+    #[serde(rename = "@Droid")]
+    pub droid: String,
+}
+
+/// `<__group>` element: team membership.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Group {
+    #[serde(rename = "@name")]
+    pub name: TeamColor,
+    #[serde(rename = "@role")]
+    pub role: TeamRole,
+}
+
+/// `<track>` element: current movement.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Track {
+    /// Course in degrees from true north.
+    #[serde(rename = "@course")]
+    pub course: f64,
+    /// Speed in meters per second.
+    #[serde(rename = "@speed")]
+    pub speed: f64,
+}
+
+/// TAK team, named by its color.
+#[derive(Copy, Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub enum TeamColor {
+    White,
+    Yellow,
+    Orange,
+    Magenta,
+    Red,
+    Maroon,
+    Purple,
+    #[serde(rename = "Dark Blue")]
+    DarkBlue,
+    Blue,
+    #[default]
+    Cyan,
+    Teal,
+    Green,
+    #[serde(rename = "Dark Green")]
+    DarkGreen,
+    Brown,
+}
+
+impl TeamColor {
+    /// Color ATAK draws the team in.
+    pub fn color(self) -> TakColor {
+        match self {
+            TeamColor::White => TakColor::WHITE,
+            TeamColor::Yellow => TakColor::YELLOW,
+            TeamColor::Orange => TakColor::from_rgb(0xff, 0x77, 0),
+            TeamColor::Magenta => TakColor::MAGENTA,
+            TeamColor::Red => TakColor::RED,
+            TeamColor::Maroon => TakColor::from_rgb(0x7f, 0, 0),
+            TeamColor::Purple => TakColor::from_rgb(0x7f, 0, 0x7f),
+            TeamColor::DarkBlue => TakColor::from_rgb(0, 0, 0x7f),
+            TeamColor::Blue => TakColor::BLUE,
+            TeamColor::Cyan => TakColor::CYAN,
+            TeamColor::Teal => TakColor::from_rgb(0, 0x7f, 0x7f),
+            TeamColor::Green => TakColor::GREEN,
+            TeamColor::DarkGreen => TakColor::from_rgb(0, 0x7f, 0),
+            TeamColor::Brown => TakColor::from_rgb(0xa0, 0x71, 0x4f),
+        }
+    }
+}
+
+/// Role of a TAK user within their team.
+#[derive(Copy, Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub enum TeamRole {
+    #[default]
+    #[serde(rename = "Team Member")]
+    TeamMember,
+    #[serde(rename = "Team Lead")]
+    TeamLead,
+    #[serde(rename = "HQ")]
+    Hq,
+    Sniper,
+    Medic,
+    #[serde(rename = "Forward Observer")]
+    ForwardObserver,
+    #[serde(rename = "RTO")]
+    Rto,
+    K9,
+}
+
+#[cfg(test)]
+mod test {
+    use crate::base::Cot;
+
+    use super::*;
+
+    #[test]
+    fn test_sa_example() {
+        let xml_path = format!(
+            "{}/src/tak/examples/sa-self.cot",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let xml_text = std::fs::read_to_string(xml_path).unwrap();
+        let cot: Cot<TakSaDetail> = quick_xml::de::from_str(&xml_text).unwrap();
+        let sa = &cot.detail;
+        let group = sa.group.as_ref().unwrap();
+        assert_eq!(group.name, TeamColor::DarkBlue);
+        assert_eq!(group.role, TeamRole::TeamLead);
+        assert_eq!(group.name.color().to_string(), "#FF00007F");
+        assert_eq!(sa.status.as_ref().unwrap().battery, Some(87));
+        assert_eq!(sa.track.as_ref().unwrap().course, 271.5);
+        assert_eq!(sa.uid.as_ref().unwrap().droid, "HOPE");
+        assert_eq!(sa.contact.endpoint.as_deref(), Some("*:-1:stcp"));
+        let takv = sa.takv.as_ref().unwrap();
+        assert_eq!(takv.platform.as_deref(), Some("ATAK-CIV"));
+
+        let xml = quick_xml::se::to_string(&cot).unwrap();
+        assert!(xml.contains(r#"name="Dark Blue""#));
+        let cot1: Cot<TakSaDetail> = quick_xml::de::from_str(&xml).unwrap();
+        assert_eq!(cot, cot1);
+    }
+}
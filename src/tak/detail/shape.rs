@@ -0,0 +1,187 @@
+use serde::{Deserialize, Serialize};
+
+use crate::base::Point;
+
+use super::{argb_hex, Color, Contact, Empty, PrecisionLocation, Remarks, TakColor, ValueElement};
+
+/// `<detail>` section for circle and ellipse shapes, e.g. drawing circles (`u-d-c-c`) and range
+/// & bearing circles (`u-r-b-c-c`).
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct TakShapeDetail {
+    pub shape: Shape,
+    #[serde(rename = "strokeColor", skip_serializing_if = "Option::is_none")]
+    pub stroke_color: Option<ValueElement<TakColor>>,
+    #[serde(rename = "strokeWeight", skip_serializing_if = "Option::is_none")]
+    pub stroke_weight: Option<ValueElement<f32>>,
+    #[serde(rename = "fillColor", skip_serializing_if = "Option::is_none")]
+    pub fill_color: Option<ValueElement<TakColor>>,
+    pub contact: Contact,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remarks: Option<Remarks>,
+    /// `<archive/>`: receivers persist the shape.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archive: Option<Empty>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub labels_on: Option<ValueElement<bool>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub precisionlocation: Option<PrecisionLocation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<Color>,
+}
+
+/// `<shape>` element: an ellipse centered on the event's `point`, and its KML style.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Shape {
+    pub ellipse: Ellipse,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link: Option<StyleLink>,
+}
+
+/// `<ellipse>` element. A circle has `major == minor`.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Ellipse {
+    /// Semi-major axis in meters.
+    #[serde(rename = "@major")]
+    pub major: f64,
+    /// Semi-minor axis in meters.
+    #[serde(rename = "@minor")]
+    pub minor: f64,
+    /// Rotation of the major axis in degrees clockwise from north.
+    #[serde(rename = "@angle")]
+    pub angle: f64,
+}
+
+impl Ellipse {
+    pub fn circle(radius: f64) -> Self {
+        Self {
+            major: radius,
+            minor: radius,
+            angle: 360.0,
+        }
+    }
+
+    /// Vertices approximating this ellipse centered on `center`. See
+    /// [`Point::ellipse_polygon()`].
+    pub fn polygon(&self, center: &Point, segments: usize) -> Vec<Point> {
+        center.ellipse_polygon(self.major, self.minor, self.angle, segments)
+    }
+}
+
+/// `<link>` to the KML style of a shape, with `type='b-x-KmlStyle'`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct StyleLink {
+    #[serde(rename = "@uid")]
+    pub uid: String,
+    #[serde(rename = "@type")]
+    pub link_type: String,
+    #[serde(rename = "@relation")]
+    pub relation: String,
+    #[serde(rename = "Style")]
+    pub style: KmlStyle,
+}
+
+impl StyleLink {
+    /// Create a style link for the shape with uid `shape_uid`.
+    pub fn new(shape_uid: &str, style: KmlStyle) -> Self {
+        Self {
+            uid: format!("{}.Style", shape_uid),
+            link_type: "b-x-KmlStyle".to_string(),
+            relation: "p-c".to_string(),
+            style,
+        }
+    }
+}
+
+/// KML `<Style>` element.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct KmlStyle {
+    #[serde(rename = "LineStyle", skip_serializing_if = "Option::is_none")]
+    pub line_style: Option<LineStyle>,
+    #[serde(rename = "PolyStyle", skip_serializing_if = "Option::is_none")]
+    pub poly_style: Option<PolyStyle>,
+}
+
+impl KmlStyle {
+    /// Create a style from the stroke and fill colors used in `<strokeColor>` and `<fillColor>`.
+    pub fn new(stroke_color: TakColor, stroke_weight: f32, fill_color: TakColor) -> Self {
+        Self {
+            line_style: Some(LineStyle {
+                color: stroke_color,
+                width: stroke_weight,
+            }),
+            poly_style: Some(PolyStyle { color: fill_color }),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct LineStyle {
+    #[serde(with = "argb_hex")]
+    pub color: TakColor,
+    pub width: f32,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct PolyStyle {
+    #[serde(with = "argb_hex")]
+    pub color: TakColor,
+}
+
+#[cfg(test)]
+mod test {
+    use crate::base::Cot;
+    use crate::detail::parse;
+
+    use super::*;
+
+    fn read_example(name: &str) -> String {
+        let xml_path = format!("{}/src/tak/examples/{}", env!("CARGO_MANIFEST_DIR"), name);
+        std::fs::read_to_string(xml_path).unwrap()
+    }
+
+    #[test]
+    fn test_shape_roundtrip() {
+        for name in ["shape-circle.cot", "range-bearing-circle.cot"] {
+            let cot: Cot<TakShapeDetail> = quick_xml::de::from_str(&read_example(name)).unwrap();
+            let ellipse = &cot.detail.shape.ellipse;
+            assert_eq!(ellipse.major, ellipse.minor, "{}", name);
+            let vertices = ellipse.polygon(&cot.point, 12);
+            assert!((cot.point.distance_to(&vertices[3]) - ellipse.major).abs() < 1e-6);
+            let style_link = cot.detail.shape.link.as_ref().unwrap();
+            assert_eq!(style_link.uid, format!("{}.Style", cot.uid));
+            assert!(style_link.style.line_style.is_some());
+            assert_eq!(cot.detail.archive, Some(Empty {}), "{}", name);
+
+            let xml = quick_xml::se::to_string(&cot).unwrap();
+            assert!(xml.contains("<remarks/><archive/><labels_on"), "{}", xml);
+            let cot1: Cot<TakShapeDetail> = quick_xml::de::from_str(&xml).unwrap();
+            assert_eq!(cot, cot1, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_kml_style_matches_atak() {
+        for name in ["shape-circle.cot", "range-bearing-circle.cot"] {
+            let cot: Cot<TakShapeDetail> = quick_xml::de::from_str(&read_example(name)).unwrap();
+            let detail = &cot.detail;
+            let style = KmlStyle::new(
+                detail.stroke_color.as_ref().unwrap().value,
+                detail.stroke_weight.as_ref().unwrap().value,
+                detail.fill_color.as_ref().unwrap().value,
+            );
+            assert_eq!(style, detail.shape.link.as_ref().unwrap().style, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_unparsed_nested_shape() {
+        let cot = parse(&read_example("shape-circle.cot")).unwrap();
+        let shape = cot
+            .detail
+            .iter()
+            .find(|l| l.starts_with("<shape>"))
+            .unwrap();
+        assert!(shape.ends_with("</shape>"));
+        assert!(shape.contains("<ellipse major='226.98412686380018'"));
+    }
+}
@@ -0,0 +1,101 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::base::{from_str, Cot};
+use crate::detail::{parse, CotUnparsedDetail};
+use crate::Error;
+
+use super::{Contact, Empty, Remarks, TakColor, TakPolygonDetail, ValueElement};
+
+/// `<detail>` section for a telestration, i.e. a multi-stroke freehand drawing (`u-d-f-m`).
+///
+/// Each stroke is a complete CoT event (normally a `u-d-f` polyline), stored as escaped XML text
+/// in the `line` attribute of a `<link>` element.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct TakTelestrationDetail {
+    #[serde(rename = "link", default)]
+    pub links: Vec<TelestrationLink>,
+    #[serde(rename = "strokeColor", skip_serializing_if = "Option::is_none")]
+    pub stroke_color: Option<ValueElement<TakColor>>,
+    #[serde(rename = "strokeWeight", skip_serializing_if = "Option::is_none")]
+    pub stroke_weight: Option<ValueElement<f32>>,
+    pub contact: Contact,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remarks: Option<Remarks>,
+    /// `<archive/>`: receivers persist the telestration.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archive: Option<Empty>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub labels_on: Option<ValueElement<bool>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<ValueElement<TakColor>>,
+}
+
+/// One stroke of a telestration: `<link line='...'/>`, with the (unescaped) XML of a CoT event.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct TelestrationLink {
+    #[serde(rename = "@line")]
+    pub line: String,
+}
+
+impl TelestrationLink {
+    /// Serialize `event` into a stroke.
+    pub fn from_event<D: Serialize>(event: &Cot<D>) -> Result<Self, Error> {
+        Ok(Self {
+            line: quick_xml::se::to_string(event)?,
+        })
+    }
+
+    /// Parse the stroke's event, leaving its `<detail>` section unparsed.
+    pub fn parse(&self) -> Result<CotUnparsedDetail, Error> {
+        parse(&self.line)
+    }
+
+    /// Parse the stroke's event, with a typed `<detail>` section.
+    pub fn parse_as<D: DeserializeOwned>(&self) -> Result<Cot<D>, Error> {
+        from_str(&self.line)
+    }
+}
+
+impl TakTelestrationDetail {
+    /// Parse all strokes, leaving their `<detail>` sections unparsed.
+    pub fn events(&self) -> Result<Vec<CotUnparsedDetail>, Error> {
+        self.links.iter().map(|l| l.parse()).collect()
+    }
+
+    /// Parse all strokes as freehand polylines.
+    pub fn strokes(&self) -> Result<Vec<Cot<TakPolygonDetail>>, Error> {
+        self.links.iter().map(|l| l.parse_as()).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_telestration_strokes() {
+        let xml_path = format!(
+            "{}/src/tak/examples/shape-telestration.cot",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let xml_text = std::fs::read_to_string(xml_path).unwrap();
+        let cot: Cot<TakTelestrationDetail> = quick_xml::de::from_str(&xml_text).unwrap();
+        assert_eq!(cot.detail.links.len(), 1);
+        assert_eq!(cot.detail.archive, Some(Empty {}));
+
+        let events = cot.detail.events().unwrap();
+        assert_eq!(events[0].cot_type, "u-d-f");
+        assert_eq!(events[0].uid, "741ad148-6d67-43fe-937b-4400cf143dc3");
+
+        let strokes = cot.detail.strokes().unwrap();
+        assert_eq!(strokes[0].detail.links.len(), 46);
+        assert!(!strokes[0].detail.is_closed());
+        assert_eq!(strokes[0].detail.contact.callsign, "Freehand 1");
+
+        let xml = quick_xml::se::to_string(&cot).unwrap();
+        assert!(xml.contains("<remarks/><archive/><labels_on"), "{}", xml);
+        let cot1: Cot<TakTelestrationDetail> = quick_xml::de::from_str(&xml).unwrap();
+        assert_eq!(cot, cot1);
+        assert_eq!(cot1.detail.strokes().unwrap(), strokes);
+    }
+}
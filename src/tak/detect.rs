@@ -1,7 +1,8 @@
+//! Support for detecting common variants of CoT messages used by TAK, and parsing them into
+//! strongly-typed structs.
+
 use quick_xml::Reader;
 
-/// Support for detecting common variants of CoT messages used by TAK, and parsing them into
-/// strongly-typed structs.
 use crate::{
     detail::{parse, CotUnparsedDetail},
     Error,
@@ -10,10 +11,16 @@ use crate::{
 /// An enum of expected message types.
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum TakCotType {
+    Chat,
+    ChatReceipt,
+    /// Emergency alert, or cancellation of one.
+    Emergency,
     GeoFence,
     Marker,
     RangeBearing,
     Route,
+    /// Self SA (position report) of a TAK user.
+    Sa,
     Shape,
     Other,
 }
@@ -42,9 +49,15 @@ pub fn detect_tak_cot_type(input: &str) -> Result<TakCotMessage, Error> {
         ("u-rb-", TakCotType::RangeBearing),
         ("b-m-r", TakCotType::Route),
         ("u-d-", TakCotType::Shape),
+        ("b-t-f-d", TakCotType::ChatReceipt),
+        ("b-t-f-r", TakCotType::ChatReceipt),
+        ("b-t-f", TakCotType::Chat),
+        ("b-a-o-", TakCotType::Emergency),
+        ("b-a-g", TakCotType::Emergency),
     ];
     let detail_tokens = [
         ("__geofence", TakCotType::GeoFence),
+        ("__group", TakCotType::Sa),
         ("usericon", TakCotType::Marker),
     ];
     // First, search detail section for clues
@@ -77,7 +90,9 @@ pub fn detect_tak_cot_type(input: &str) -> Result<TakCotMessage, Error> {
 pub fn parse_cot_msg_type(text: &str) -> Result<String, Error> {
     match xml_first_element_w_attr(text, "event", "type") {
         Ok(Some(val)) => Ok(val),
-        _ => Err(Error::BadField("No element 'event' with attribute 'type'")),
+        _ => Err(Error::BadField(
+            "No element 'event' with attribute 'type'".into(),
+        )),
     }
 }
 
@@ -92,13 +107,13 @@ pub fn xml_first_element_w_attr(
     loop {
         match reader.read_event()? {
             // Parse attribute `type` in the `event` element.
-            quick_xml::events::Event::Start(ref e) => {
-                if e.name().into_inner() == elt_name.as_bytes() {
-                    for attr in e.attributes() {
-                        let attr = attr?;
-                        if attr.key.into_inner() == attr_name.as_bytes() {
-                            return Ok(Some(String::from_utf8_lossy(&attr.value).to_string()));
-                        }
+            quick_xml::events::Event::Start(ref e)
+                if e.name().into_inner() == elt_name.as_bytes() =>
+            {
+                for attr in e.attributes() {
+                    let attr = attr?;
+                    if attr.key.into_inner() == attr_name.as_bytes() {
+                        return Ok(Some(String::from_utf8_lossy(&attr.value).to_string()));
                     }
                 }
             }
@@ -111,6 +126,8 @@ pub fn xml_first_element_w_attr(
 
 #[cfg(test)]
 mod test {
+    use crate::tak::create::{chat_message, chat_receipt};
+    use crate::tak::detail::{ChatParticipant, ChatReceiptKind, ALL_CHAT_ROOMS};
     use crate::tak::test::get_xml_examples;
 
     use super::{detect_tak_cot_type, TakCotType};
@@ -121,7 +138,11 @@ mod test {
         for res in examples {
             let (filename, cot_xml) = res.unwrap();
             let cot = detect_tak_cot_type(&cot_xml).unwrap();
-            if filename.starts_with("geo-fence") {
+            if filename.starts_with("chat-") {
+                assert_type(&filename, cot.cot_type, TakCotType::Chat);
+            } else if filename.starts_with("emergency-") {
+                assert_type(&filename, cot.cot_type, TakCotType::Emergency);
+            } else if filename.starts_with("geo-fence") {
                 assert_type(&filename, cot.cot_type, TakCotType::GeoFence);
             } else if filename.starts_with("marker-") {
                 assert_type(&filename, cot.cot_type, TakCotType::Marker);
@@ -129,12 +150,31 @@ mod test {
                 assert_type(&filename, cot.cot_type, TakCotType::RangeBearing);
             } else if filename.starts_with("route") {
                 assert_type(&filename, cot.cot_type, TakCotType::Route);
+            } else if filename.starts_with("sa-") {
+                assert_type(&filename, cot.cot_type, TakCotType::Sa);
             } else if filename.starts_with("shape-") {
                 assert_type(&filename, cot.cot_type, TakCotType::Shape);
             }
         }
     }
 
+    #[test]
+    fn test_detect_chat_receipt() {
+        let from = ChatParticipant::new("ANDROID-1", "HOPE");
+        let msg = chat_message(&from, ALL_CHAT_ROOMS, "hello");
+        let xml = quick_xml::se::to_string(&msg).unwrap();
+        assert_eq!(
+            detect_tak_cot_type(&xml).unwrap().cot_type,
+            TakCotType::Chat
+        );
+        for kind in [ChatReceiptKind::Delivered, ChatReceiptKind::Read] {
+            let receipt = chat_receipt(&msg, &from, kind);
+            let xml = quick_xml::se::to_string(&receipt).unwrap();
+            let detected = detect_tak_cot_type(&xml).unwrap().cot_type;
+            assert_eq!(detected, TakCotType::ChatReceipt);
+        }
+    }
+
     fn assert_type(filename: &str, actual: TakCotType, expected: TakCotType) {
         assert_eq!(
             actual, expected,
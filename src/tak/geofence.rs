@@ -0,0 +1,352 @@
+//! Geofence evaluation: detect when tracked items enter or leave a geofence.
+//!
+//! ```rust
+//! # use cot_proto::base::Cot;
+//! # use cot_proto::tak::detail::{GeoFenceDetail, TakMarkerDetail};
+//! # use cot_proto::tak::geofence::GeoFenceEvaluator;
+//! # fn run(fence: &Cot<GeoFenceDetail>, tracks: &[Cot<TakMarkerDetail>]) {
+//! let mut evaluator = GeoFenceEvaluator::new(fence).unwrap();
+//! for notification in evaluator.evaluate(tracks) {
+//!     println!("{} {:?} {}", notification.track_uid, notification.transition, notification.fence_uid);
+//! }
+//! # }
+//! ```
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+
+use crate::base::{Cot, Point, UNKNOWN_VALUE};
+use crate::Error;
+
+use super::detail::{Ellipse, GeoFence, GeoFenceDetail, GeoFenceMonitor, GeoFenceTrigger};
+
+/// Area covered by a geofence.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FenceArea {
+    Ellipse { center: Point, ellipse: Ellipse },
+    Polygon(Vec<Point>),
+}
+
+impl FenceArea {
+    /// Whether `point` is inside the area, ignoring altitude.
+    pub fn contains(&self, point: &Point) -> bool {
+        match self {
+            FenceArea::Ellipse { center, ellipse } => {
+                let distance = center.distance_to(point);
+                if distance == 0.0 {
+                    return true;
+                }
+                // Position in a frame aligned with the ellipse's major axis.
+                let theta = (center.bearing_to(point) - ellipse.angle).to_radians();
+                let along = distance * theta.cos() / ellipse.major;
+                let across = distance * theta.sin() / ellipse.minor;
+                along * along + across * across <= 1.0
+            }
+            FenceArea::Polygon(vertices) => {
+                // Ray casting, treating lat/lon as planar coordinates. Longitudes are unwrapped
+                // around the first vertex so that fences across the antimeridian stay contiguous.
+                let Some(first) = vertices.first() else {
+                    return false;
+                };
+                let lons: Vec<f64> = vertices
+                    .iter()
+                    .map(|v| unwrap_lon(v.lon, first.lon))
+                    .collect();
+                let lon = unwrap_lon(point.lon, first.lon);
+                let mut inside = false;
+                let n = vertices.len();
+                for i in 0..n {
+                    let j = (i + n - 1) % n;
+                    let (a, b) = (&vertices[i], &vertices[j]);
+                    let (a_lon, b_lon) = (lons[i], lons[j]);
+                    if (a.lat > point.lat) != (b.lat > point.lat)
+                        && lon < (b_lon - a_lon) * (point.lat - a.lat) / (b.lat - a.lat) + a_lon
+                    {
+                        inside = !inside;
+                    }
+                }
+                inside
+            }
+        }
+    }
+
+    /// Approximate center of the area.
+    pub fn center(&self) -> Point {
+        match self {
+            FenceArea::Ellipse { center, .. } => center.clone(),
+            FenceArea::Polygon(vertices) => {
+                let n = vertices.len().max(1) as f64;
+                let first_lon = vertices.first().map_or(0.0, |p| p.lon);
+                let lon = vertices
+                    .iter()
+                    .map(|p| unwrap_lon(p.lon, first_lon))
+                    .sum::<f64>()
+                    / n;
+                Point::from_lat_lon(
+                    vertices.iter().map(|p| p.lat).sum::<f64>() / n,
+                    unwrap_lon(lon, 0.0),
+                )
+            }
+        }
+    }
+}
+
+/// `lon` shifted by a multiple of 360° to within 180° of `reference`.
+fn unwrap_lon(lon: f64, reference: f64) -> f64 {
+    reference + (lon - reference + 540.0).rem_euclid(360.0) - 180.0
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Transition {
+    Entered,
+    Exited,
+}
+
+/// Notification that a tracked item crossed a geofence boundary.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeoFenceNotification {
+    pub fence_uid: String,
+    pub track_uid: String,
+    pub transition: Transition,
+    /// Time of the track event which crossed the boundary.
+    pub time: DateTime<Utc>,
+    pub point: Point,
+}
+
+/// Evaluates track events against a single geofence, keeping track of which items are inside.
+///
+/// The first event seen for an item only records whether it is inside; notifications are emitted
+/// when a later event crosses the boundary in a direction matching the fence's trigger.
+#[derive(Clone, Debug)]
+pub struct GeoFenceEvaluator {
+    fence_uid: String,
+    area: FenceArea,
+    geofence: GeoFence,
+    custom_uids: HashSet<String>,
+    inside: HashMap<String, bool>,
+}
+
+impl GeoFenceEvaluator {
+    pub fn new(fence: &Cot<GeoFenceDetail>) -> Result<Self, Error> {
+        let area = match &fence.detail.shape {
+            Some(shape) => FenceArea::Ellipse {
+                center: fence.point.clone(),
+                ellipse: shape.ellipse.clone(),
+            },
+            None if fence.detail.links.len() >= 3 => FenceArea::Polygon(
+                fence
+                    .detail
+                    .links
+                    .iter()
+                    .map(|l| l.point.to_point())
+                    .collect(),
+            ),
+            None => return Err(Error::BadField("geofence has no shape or vertices".into())),
+        };
+        Ok(Self {
+            fence_uid: fence.uid.clone(),
+            area,
+            geofence: fence.detail.geofence.clone(),
+            custom_uids: HashSet::new(),
+            inside: HashMap::new(),
+        })
+    }
+
+    /// Set the item uids monitored by a fence with [`GeoFenceMonitor::Custom`].
+    pub fn with_custom_uids<I: IntoIterator<Item = String>>(mut self, uids: I) -> Self {
+        self.custom_uids = uids.into_iter().collect();
+        self
+    }
+
+    pub fn area(&self) -> &FenceArea {
+        &self.area
+    }
+
+    /// Whether the fence monitors items with the given uid and CoT type.
+    pub fn monitors(&self, uid: &str, cot_type: &str) -> bool {
+        match self.geofence.monitor {
+            GeoFenceMonitor::All => cot_type.starts_with("a-"),
+            GeoFenceMonitor::TakUsers => cot_type.starts_with("a-f-G-U-C"),
+            GeoFenceMonitor::Friendly => cot_type.starts_with("a-f-"),
+            GeoFenceMonitor::Hostile => cot_type.starts_with("a-h-"),
+            GeoFenceMonitor::Custom => self.custom_uids.contains(uid),
+        }
+    }
+
+    /// Whether `point` is inside the fence, including its elevation bounds and bounding sphere.
+    ///
+    /// Elevation bounds are ignored when the point's altitude is unknown.
+    pub fn contains(&self, point: &Point) -> bool {
+        if let Some(radius) = self.geofence.bounding_sphere {
+            if self.area.center().distance_to(point) > radius {
+                return false;
+            }
+        }
+        if self.geofence.elevation_monitored && point.hae != UNKNOWN_VALUE {
+            let hae = point.hae as f64;
+            if self.geofence.min_elevation.is_some_and(|min| hae < min)
+                || self.geofence.max_elevation.is_some_and(|max| hae > max)
+            {
+                return false;
+            }
+        }
+        self.area.contains(point)
+    }
+
+    /// Process one track event, returning a notification if it crossed the fence boundary.
+    pub fn update<D>(&mut self, track: &Cot<D>) -> Option<GeoFenceNotification> {
+        if track.uid == self.fence_uid || !self.monitors(&track.uid, &track.cot_type) {
+            return None;
+        }
+        let inside = self.contains(&track.point);
+        let was_inside = self.inside.insert(track.uid.clone(), inside)?;
+        let transition = match (was_inside, inside) {
+            (false, true) => Transition::Entered,
+            (true, false) => Transition::Exited,
+            _ => return None,
+        };
+        let triggered = match self.geofence.trigger {
+            GeoFenceTrigger::Entry => transition == Transition::Entered,
+            GeoFenceTrigger::Exit => transition == Transition::Exited,
+            GeoFenceTrigger::Both => true,
+        };
+        triggered.then(|| GeoFenceNotification {
+            fence_uid: self.fence_uid.clone(),
+            track_uid: track.uid.clone(),
+            transition,
+            time: *track.time,
+            point: track.point.clone(),
+        })
+    }
+
+    /// Process a stream of track events, in order.
+    pub fn evaluate<'a, D: 'a, I>(&mut self, tracks: I) -> Vec<GeoFenceNotification>
+    where
+        I: IntoIterator<Item = &'a Cot<D>>,
+    {
+        tracks.into_iter().filter_map(|t| self.update(t)).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::tak::detail::{LinkPoint, TakMarkerDetail, VertexLink};
+
+    use super::*;
+
+    fn fence() -> Cot<GeoFenceDetail> {
+        let xml_path = format!(
+            "{}/src/tak/examples/geo-fence.cot",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        quick_xml::de::from_str(&std::fs::read_to_string(xml_path).unwrap()).unwrap()
+    }
+
+    fn track(uid: &str, cot_type: &str, point: Point) -> Cot<TakMarkerDetail> {
+        Cot {
+            uid: uid.to_string(),
+            cot_type: cot_type.to_string(),
+            point,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_circle_fence_entry() {
+        let fence = fence();
+        let center = fence.point.clone();
+        let mut evaluator = GeoFenceEvaluator::new(&fence).unwrap();
+        let outside = center.destination(90.0, 500.0);
+        let inside = center.destination(90.0, 100.0);
+        let tracks = [
+            track("t1", "a-f-G-U-C", outside.clone()),
+            track("t1", "a-f-G-U-C", inside.clone()),
+            track("t1", "a-f-G-U-C", outside.clone()),
+            track("t2", "a-h-G", inside.clone()),
+            track("marker", "b-m-p-s-m", outside.clone()),
+            track("marker", "b-m-p-s-m", inside.clone()),
+        ];
+        let notifications = evaluator.evaluate(&tracks);
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].track_uid, "t1");
+        assert_eq!(notifications[0].transition, Transition::Entered);
+    }
+
+    #[test]
+    fn test_elevation_bounds() {
+        let fence = fence();
+        let mut evaluator = GeoFenceEvaluator::new(&fence).unwrap();
+        let mut high = fence.point.clone();
+        high.hae = 1000.0;
+        let mut low = fence.point.clone();
+        low.hae = 100.0;
+        assert!(!evaluator.contains(&high));
+        assert!(evaluator.contains(&low));
+        assert!(evaluator.update(&track("a", "a-f-A", high)).is_none());
+        let n = evaluator.update(&track("a", "a-f-A", low)).unwrap();
+        assert_eq!(n.transition, Transition::Entered);
+    }
+
+    #[test]
+    fn test_polygon_fence_exit() {
+        let mut fence = fence();
+        fence.detail.shape = None;
+        fence.detail.links = [(0.0, 0.0), (0.0, 1.0), (1.0, 1.0), (1.0, 0.0)]
+            .iter()
+            .map(|(lat, lon)| VertexLink::from(LinkPoint::new(*lat, *lon)))
+            .collect();
+        fence.detail.geofence.trigger = GeoFenceTrigger::Exit;
+        fence.detail.geofence.monitor = GeoFenceMonitor::Custom;
+        fence.detail.geofence.elevation_monitored = false;
+        fence.detail.geofence.bounding_sphere = None;
+        let mut evaluator = GeoFenceEvaluator::new(&fence)
+            .unwrap()
+            .with_custom_uids(["boat".to_string()]);
+        let tracks = [
+            track("boat", "a-n-S", Point::from_lat_lon(0.5, 0.5)),
+            track("boat", "a-n-S", Point::from_lat_lon(1.5, 0.5)),
+            track("other", "a-n-S", Point::from_lat_lon(0.5, 0.5)),
+            track("other", "a-n-S", Point::from_lat_lon(1.5, 0.5)),
+        ];
+        let notifications = evaluator.evaluate(&tracks);
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].track_uid, "boat");
+        assert_eq!(notifications[0].transition, Transition::Exited);
+    }
+
+    #[test]
+    fn test_polygon_across_antimeridian() {
+        let area = FenceArea::Polygon(
+            [(-1.0, 179.0), (-1.0, -179.0), (1.0, -179.0), (1.0, 179.0)]
+                .iter()
+                .map(|(lat, lon)| Point::from_lat_lon(*lat, *lon))
+                .collect(),
+        );
+        assert!(area.contains(&Point::from_lat_lon(0.0, 179.5)));
+        assert!(area.contains(&Point::from_lat_lon(0.0, -179.5)));
+        assert!(area.contains(&Point::from_lat_lon(0.0, 180.0)));
+        assert!(!area.contains(&Point::from_lat_lon(0.0, 0.0)));
+        assert!(!area.contains(&Point::from_lat_lon(0.0, 178.5)));
+        assert!(!area.contains(&Point::from_lat_lon(0.0, -178.5)));
+        let center = area.center();
+        assert_eq!(center.lat, 0.0);
+        assert!((center.lon.abs() - 180.0).abs() < 1e-9, "{}", center.lon);
+    }
+
+    #[test]
+    fn test_ellipse_contains() {
+        let center = Point::from_lat_lon(10.0, 10.0);
+        let area = FenceArea::Ellipse {
+            center: center.clone(),
+            ellipse: Ellipse {
+                major: 1000.0,
+                minor: 100.0,
+                angle: 90.0,
+            },
+        };
+        assert!(area.contains(&center.destination(90.0, 900.0)));
+        assert!(!area.contains(&center.destination(0.0, 900.0)));
+        assert!(area.contains(&center.destination(0.0, 90.0)));
+    }
+}
@@ -0,0 +1,198 @@
+//! Reading ATAK iconset zip files, to resolve a marker's [`IconPath`] to its icon image.
+//!
+//! An iconset zip contains an `iconset.xml` describing the set, and the icon images stored as
+//! `<group>/<filename>`.
+//!
+//! ```rust,no_run
+//! # use cot_proto::tak::detail::IconPath;
+//! # use cot_proto::tak::iconset::Iconset;
+//! # fn run(path: &IconPath) -> Result<(), cot_proto::Error> {
+//! let mut iconset = Iconset::open("google.zip")?;
+//! if let Some(png) = iconset.icon(path)? {
+//!     std::fs::write("icon.png", png)?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fs::File;
+use std::io::{Read, Seek};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use zip::ZipArchive;
+
+use crate::base::from_str;
+use crate::Error;
+
+use super::detail::IconPath;
+
+/// Name of the iconset description file within an iconset zip.
+pub const ICONSET_XML: &str = "iconset.xml";
+
+/// Contents of `iconset.xml`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename = "iconset")]
+pub struct IconsetInfo {
+    #[serde(rename = "@version", skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(rename = "@name")]
+    pub name: String,
+    /// Iconset uid, the first component of [`IconPath::Custom`] paths.
+    #[serde(rename = "@uid")]
+    pub uid: String,
+    #[serde(rename = "@default_group", skip_serializing_if = "Option::is_none")]
+    pub default_group: Option<String>,
+    #[serde(rename = "@default_friendly", skip_serializing_if = "Option::is_none")]
+    pub default_friendly: Option<String>,
+    #[serde(rename = "@default_hostile", skip_serializing_if = "Option::is_none")]
+    pub default_hostile: Option<String>,
+    #[serde(rename = "@default_neutral", skip_serializing_if = "Option::is_none")]
+    pub default_neutral: Option<String>,
+    #[serde(rename = "@default_unknown", skip_serializing_if = "Option::is_none")]
+    pub default_unknown: Option<String>,
+    #[serde(rename = "@skip_resize", skip_serializing_if = "Option::is_none")]
+    pub skip_resize: Option<bool>,
+    #[serde(rename = "icon", default)]
+    pub icons: Vec<IconInfo>,
+}
+
+/// `<icon>` element of `iconset.xml`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct IconInfo {
+    /// Filename of the icon image.
+    #[serde(rename = "@name")]
+    pub name: String,
+    #[serde(rename = "@groupName", skip_serializing_if = "Option::is_none")]
+    pub group_name: Option<String>,
+    /// CoT type to use for markers with this icon.
+    #[serde(rename = "@type2525b", skip_serializing_if = "Option::is_none")]
+    pub type_2525b: Option<String>,
+}
+
+/// An opened iconset zip file.
+pub struct Iconset<R> {
+    pub info: IconsetInfo,
+    archive: ZipArchive<R>,
+    /// Directory containing `iconset.xml` within the zip, with trailing `/`, if not at the root.
+    prefix: String,
+}
+
+impl Iconset<File> {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::new(File::open(path)?)
+    }
+}
+
+impl<R: Read + Seek> Iconset<R> {
+    /// Read an iconset zip, parsing its `iconset.xml`.
+    pub fn new(reader: R) -> Result<Self, Error> {
+        let mut archive = ZipArchive::new(reader)?;
+        // Some iconsets wrap their contents in a top-level directory.
+        let xml_name = archive
+            .file_names()
+            .filter(|name| *name == ICONSET_XML || name.ends_with("/iconset.xml"))
+            .min_by_key(|name| name.len())
+            .ok_or(Error::BadField("iconset zip has no iconset.xml".into()))?
+            .to_string();
+        let prefix = xml_name.trim_end_matches(ICONSET_XML).to_string();
+        let mut xml = String::new();
+        archive.by_name(&xml_name)?.read_to_string(&mut xml)?;
+        let info = from_str(&xml)?;
+        Ok(Self {
+            info,
+            archive,
+            prefix,
+        })
+    }
+
+    /// Name of the zip entry holding the image for `path`, if it is an icon of this iconset.
+    pub fn entry_name(&self, path: &IconPath) -> Option<String> {
+        match path {
+            IconPath::Custom {
+                iconset_uid,
+                group,
+                filename,
+            } if *iconset_uid == self.info.uid => {
+                let name = format!("{}{}/{}", self.prefix, group, filename);
+                self.archive.index_for_name(&name).map(|_| name)
+            }
+            _ => None,
+        }
+    }
+
+    /// Read the image for `path`. Returns `None` if it isn't an icon of this iconset.
+    pub fn icon(&mut self, path: &IconPath) -> Result<Option<Vec<u8>>, Error> {
+        let Some(name) = self.entry_name(path) else {
+            return Ok(None);
+        };
+        let mut file = self.archive.by_name(&name)?;
+        let mut image = Vec::with_capacity(file.size() as usize);
+        file.read_to_end(&mut image)?;
+        Ok(Some(image))
+    }
+
+    /// Icon path for an icon of this iconset.
+    pub fn icon_path(&self, icon: &IconInfo) -> IconPath {
+        let group = icon
+            .group_name
+            .as_ref()
+            .or(self.info.default_group.as_ref())
+            .cloned()
+            .unwrap_or_default();
+        IconPath::Custom {
+            iconset_uid: self.info.uid.clone(),
+            group,
+            filename: icon.name.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::{Cursor, Write};
+
+    use zip::write::SimpleFileOptions;
+    use zip::{CompressionMethod, ZipWriter};
+
+    use super::*;
+
+    const UID: &str = "f7f71666-8b28-4b57-9fbb-e38e61d33b79";
+
+    fn iconset_zip(prefix: &str) -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(vec![]));
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+        zip.start_file(format!("{}iconset.xml", prefix), options)
+            .unwrap();
+        write!(
+            zip,
+            r#"<?xml version='1.0' encoding='UTF-8' standalone='yes'?>
+<iconset version="1" name="Google" default_group="Google" uid="{}" skip_resize="false">
+  <icon name="hiker.png" groupName="Google" type2525b="a-n-G"/>
+</iconset>"#,
+            UID
+        )
+        .unwrap();
+        zip.start_file(format!("{}Google/hiker.png", prefix), options)
+            .unwrap();
+        zip.write_all(b"PNG").unwrap();
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_iconset_resolve() {
+        for prefix in ["", "google/"] {
+            let mut iconset = Iconset::new(Cursor::new(iconset_zip(prefix))).unwrap();
+            assert_eq!(iconset.info.name, "Google");
+            assert_eq!(iconset.info.icons.len(), 1);
+            let path = iconset.icon_path(&iconset.info.icons[0].clone());
+            assert_eq!(path.to_string(), format!("{}/Google/hiker.png", UID));
+            assert_eq!(iconset.icon(&path).unwrap().unwrap(), b"PNG");
+
+            let other: IconPath = "COT_MAPPING_2525B/a-u/a-u-G".parse().unwrap();
+            assert!(iconset.icon(&other).unwrap().is_none());
+            let missing: IconPath = format!("{}/Google/missing.png", UID).parse().unwrap();
+            assert!(iconset.icon(&missing).unwrap().is_none());
+        }
+    }
+}
@@ -0,0 +1,433 @@
+//! Conversion between CoT atom types (e.g. `a-h-A-M-F-U-M`) and MIL-STD-2525 symbol
+//! identification codes (SIDCs).
+//!
+//! A CoT atom type is `a-<affiliation>-<battle dimension>[-<function id char>...]`, where the
+//! function id chars are those of the 2525B/C SIDC, so 2525B/C conversion is lossless:
+//!
+//! ```rust
+//! # use cot_proto::tak::milsym::{cot_type_to_sidc, sidc_to_cot_type};
+//! assert_eq!(cot_type_to_sidc("a-h-A-M-F-U-M").unwrap(), "SHAPMFUM-------");
+//! assert_eq!(sidc_to_cot_type("SFGPUCI--------").unwrap(), "a-f-G-U-C-I");
+//! ```
+//!
+//! 2525D codes carry the same affiliation and dimension in their header, but identify the symbol
+//! with a numeric entity code which doesn't correspond to CoT function ids. Only the header is
+//! converted; see [`Sidc::to_2525d()`].
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::Error;
+
+/// Standard identity (affiliation) of a symbol.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Affiliation {
+    Pending,
+    Unknown,
+    AssumedFriend,
+    Friend,
+    Neutral,
+    Suspect,
+    Hostile,
+    Joker,
+    Faker,
+    NoneSpecified,
+}
+
+impl Affiliation {
+    const ALL: [Affiliation; 10] = [
+        Affiliation::Pending,
+        Affiliation::Unknown,
+        Affiliation::AssumedFriend,
+        Affiliation::Friend,
+        Affiliation::Neutral,
+        Affiliation::Suspect,
+        Affiliation::Hostile,
+        Affiliation::Joker,
+        Affiliation::Faker,
+        Affiliation::NoneSpecified,
+    ];
+
+    /// Affiliation letter in a CoT type, e.g. `f` in `a-f-G`.
+    pub fn cot_char(self) -> char {
+        self.sidc_char().to_ascii_lowercase()
+    }
+
+    /// Affiliation letter in a 2525B/C SIDC, e.g. `F` in `SFGP...`.
+    pub fn sidc_char(self) -> char {
+        match self {
+            Affiliation::Pending => 'P',
+            Affiliation::Unknown => 'U',
+            Affiliation::AssumedFriend => 'A',
+            Affiliation::Friend => 'F',
+            Affiliation::Neutral => 'N',
+            Affiliation::Suspect => 'S',
+            Affiliation::Hostile => 'H',
+            Affiliation::Joker => 'J',
+            Affiliation::Faker => 'K',
+            Affiliation::NoneSpecified => 'O',
+        }
+    }
+
+    pub fn from_cot_char(c: char) -> Option<Self> {
+        Self::ALL.into_iter().find(|a| a.cot_char() == c)
+    }
+
+    /// Parse a 2525B/C affiliation letter, treating exercise affiliations as their real-world
+    /// counterparts.
+    pub fn from_sidc_char(c: char) -> Option<Self> {
+        let c = match c.to_ascii_uppercase() {
+            'G' => 'P',
+            'W' => 'U',
+            'M' => 'A',
+            'D' => 'F',
+            'L' => 'N',
+            c => c,
+        };
+        Self::ALL.into_iter().find(|a| a.sidc_char() == c)
+    }
+
+    /// 2525D standard identity digits: context (0 reality, 1 exercise) and identity.
+    fn to_2525d(self) -> Option<&'static str> {
+        match self {
+            Affiliation::Pending => Some("00"),
+            Affiliation::Unknown => Some("01"),
+            Affiliation::AssumedFriend => Some("02"),
+            Affiliation::Friend => Some("03"),
+            Affiliation::Neutral => Some("04"),
+            Affiliation::Suspect => Some("05"),
+            Affiliation::Hostile => Some("06"),
+            Affiliation::Joker => Some("15"),
+            Affiliation::Faker => Some("16"),
+            Affiliation::NoneSpecified => None,
+        }
+    }
+
+    fn from_2525d(context: u8, identity: u8) -> Option<Self> {
+        match (context, identity) {
+            (1, 5) => Some(Affiliation::Joker),
+            (1, 6) => Some(Affiliation::Faker),
+            (_, 0) => Some(Affiliation::Pending),
+            (_, 1) => Some(Affiliation::Unknown),
+            (_, 2) => Some(Affiliation::AssumedFriend),
+            (_, 3) => Some(Affiliation::Friend),
+            (_, 4) => Some(Affiliation::Neutral),
+            (_, 5) => Some(Affiliation::Suspect),
+            (_, 6) => Some(Affiliation::Hostile),
+            _ => None,
+        }
+    }
+}
+
+/// Battle dimension of a symbol.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BattleDimension {
+    Space,
+    Air,
+    Ground,
+    SeaSurface,
+    Subsurface,
+    SpecialOperations,
+    Other,
+}
+
+impl BattleDimension {
+    const ALL: [BattleDimension; 7] = [
+        BattleDimension::Space,
+        BattleDimension::Air,
+        BattleDimension::Ground,
+        BattleDimension::SeaSurface,
+        BattleDimension::Subsurface,
+        BattleDimension::SpecialOperations,
+        BattleDimension::Other,
+    ];
+
+    /// Dimension letter in a CoT type, e.g. `G` in `a-f-G`.
+    pub fn cot_char(self) -> char {
+        match self {
+            BattleDimension::Other => 'X',
+            d => d.sidc_char(),
+        }
+    }
+
+    /// Dimension letter in a 2525B/C SIDC, e.g. `G` in `SFGP...`.
+    pub fn sidc_char(self) -> char {
+        match self {
+            BattleDimension::Space => 'P',
+            BattleDimension::Air => 'A',
+            BattleDimension::Ground => 'G',
+            BattleDimension::SeaSurface => 'S',
+            BattleDimension::Subsurface => 'U',
+            BattleDimension::SpecialOperations => 'F',
+            BattleDimension::Other => 'Z',
+        }
+    }
+
+    pub fn from_cot_char(c: char) -> Option<Self> {
+        Self::ALL.into_iter().find(|d| d.cot_char() == c)
+    }
+
+    pub fn from_sidc_char(c: char) -> Option<Self> {
+        let c = c.to_ascii_uppercase();
+        Self::ALL.into_iter().find(|d| d.sidc_char() == c)
+    }
+}
+
+/// Length of a 2525B/C SIDC.
+pub const SIDC_LEN: usize = 15;
+
+/// Number of function id characters in a 2525B/C SIDC.
+const FUNCTION_ID_LEN: usize = 6;
+
+/// A warfighting symbol identification code, i.e. the parts of a 2525B/C SIDC which CoT types
+/// represent.
+///
+/// Displays as a 15-character 2525B/C SIDC, with unused positions as `-`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Sidc {
+    pub affiliation: Affiliation,
+    pub dimension: BattleDimension,
+    /// Anticipated/planned rather than present. CoT types have no equivalent.
+    pub planned: bool,
+    /// Function id characters, e.g. `UCI` for infantry, without trailing `-`.
+    pub function_id: String,
+}
+
+impl Sidc {
+    /// Parse a CoT atom type, e.g. `a-f-G-U-C-I`.
+    pub fn from_cot_type(cot_type: &str) -> Result<Self, Error> {
+        let mut parts = cot_type.split('-');
+        if parts.next() != Some("a") {
+            return Err(Error::BadField("CoT type is not an atom (a-...)".into()));
+        }
+        let affiliation = parts
+            .next()
+            .and_then(single_char)
+            .and_then(Affiliation::from_cot_char)
+            .ok_or(Error::BadField("CoT type has unknown affiliation".into()))?;
+        let dimension = parts
+            .next()
+            .and_then(single_char)
+            .and_then(BattleDimension::from_cot_char)
+            .ok_or(Error::BadField(
+                "CoT type has unknown battle dimension".into(),
+            ))?;
+        let function_id: String = parts.collect();
+        if function_id.len() > FUNCTION_ID_LEN || !function_id.chars().all(|c| c.is_alphanumeric())
+        {
+            return Err(Error::BadField("CoT type has invalid function id".into()));
+        }
+        Ok(Self {
+            affiliation,
+            dimension,
+            planned: false,
+            function_id,
+        })
+    }
+
+    /// CoT atom type for this symbol, e.g. `a-f-G-U-C-I`.
+    pub fn to_cot_type(&self) -> String {
+        let mut cot_type = format!(
+            "a-{}-{}",
+            self.affiliation.cot_char(),
+            self.dimension.cot_char()
+        );
+        for c in self.function_id.chars() {
+            cot_type.push('-');
+            cot_type.push(c);
+        }
+        cot_type
+    }
+
+    /// 2525D 20-digit SIDC for this symbol.
+    ///
+    /// Only the header (version 10, standard identity, symbol set and status) is converted; the
+    /// entity is left as `000000` (unspecified), since 2525D entity codes don't map onto CoT
+    /// function ids. Returns `None` for affiliations or dimensions with no 2525D equivalent.
+    pub fn to_2525d(&self) -> Option<String> {
+        let identity = self.affiliation.to_2525d()?;
+        let symbol_set = match (self.dimension, self.function_id.chars().next()) {
+            (BattleDimension::Air, Some('W')) => "02",
+            (BattleDimension::Air, _) => "01",
+            (BattleDimension::Space, _) => "05",
+            (BattleDimension::Ground, Some('E')) => "15",
+            (BattleDimension::Ground, Some('I')) => "20",
+            (BattleDimension::Ground | BattleDimension::SpecialOperations, _) => "10",
+            (BattleDimension::SeaSurface, _) => "30",
+            (BattleDimension::Subsurface, _) => "35",
+            (BattleDimension::Other, _) => return None,
+        };
+        let status = if self.planned { '1' } else { '0' };
+        Some(format!(
+            "10{}{}{}0000000000000",
+            identity, symbol_set, status
+        ))
+    }
+
+    /// Parse the header of a 2525D 20-digit SIDC. The entity is ignored, except that land
+    /// equipment and installations get function ids `E` and `I` respectively.
+    pub fn from_2525d(sidc: &str) -> Result<Self, Error> {
+        if sidc.len() != 20 || !sidc.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(Error::BadField("2525D SIDC must be 20 digits".into()));
+        }
+        let digit = |i: usize| sidc.as_bytes()[i] - b'0';
+        let affiliation = Affiliation::from_2525d(digit(2), digit(3)).ok_or(Error::BadField(
+            "2525D SIDC has unknown standard identity".into(),
+        ))?;
+        let (dimension, function_id) = match &sidc[4..6] {
+            "01" => (BattleDimension::Air, ""),
+            "02" => (BattleDimension::Air, "W"),
+            "05" | "06" => (BattleDimension::Space, ""),
+            "10" | "11" => (BattleDimension::Ground, ""),
+            "15" => (BattleDimension::Ground, "E"),
+            "20" => (BattleDimension::Ground, "I"),
+            "30" => (BattleDimension::SeaSurface, ""),
+            "35" | "36" => (BattleDimension::Subsurface, ""),
+            _ => {
+                return Err(Error::BadField(
+                    "2525D SIDC has unsupported symbol set".into(),
+                ))
+            }
+        };
+        Ok(Self {
+            affiliation,
+            dimension,
+            planned: digit(6) == 1,
+            function_id: function_id.to_string(),
+        })
+    }
+}
+
+fn single_char(s: &str) -> Option<char> {
+    let mut chars = s.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    }
+}
+
+impl FromStr for Sidc {
+    type Err = Error;
+
+    /// Parse a 2525B/C SIDC, of which only the warfighting (`S`) coding scheme is supported.
+    /// Trailing positions may be omitted.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let chars: Vec<char> = s.chars().collect();
+        if chars.len() < 4 || chars.len() > SIDC_LEN {
+            return Err(Error::BadField("SIDC must be 4 to 15 characters".into()));
+        }
+        if !chars[0].eq_ignore_ascii_case(&'S') {
+            return Err(Error::BadField(
+                "only warfighting (S) SIDCs are supported".into(),
+            ));
+        }
+        let affiliation = Affiliation::from_sidc_char(chars[1])
+            .ok_or(Error::BadField("SIDC has unknown affiliation".into()))?;
+        let dimension = BattleDimension::from_sidc_char(chars[2])
+            .ok_or(Error::BadField("SIDC has unknown battle dimension".into()))?;
+        let planned = match chars[3].to_ascii_uppercase() {
+            // Present, possibly with a 2525C operational condition: fully capable, damaged,
+            // destroyed or full to capacity.
+            'P' | '-' | 'C' | 'D' | 'X' | 'F' => false,
+            'A' => true,
+            _ => return Err(Error::BadField("SIDC has unknown status".into())),
+        };
+        let function_id: String = chars[4..]
+            .iter()
+            .take(FUNCTION_ID_LEN)
+            .take_while(|c| **c != '-')
+            .map(|c| c.to_ascii_uppercase())
+            .collect();
+        Ok(Self {
+            affiliation,
+            dimension,
+            planned,
+            function_id,
+        })
+    }
+}
+
+impl fmt::Display for Sidc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = if self.planned { 'A' } else { 'P' };
+        let header = format!(
+            "S{}{}{}{}",
+            self.affiliation.sidc_char(),
+            self.dimension.sidc_char(),
+            status,
+            self.function_id
+        );
+        write!(f, "{:-<width$}", header, width = SIDC_LEN)
+    }
+}
+
+/// Convert a CoT atom type to a 15-character 2525B/C SIDC.
+pub fn cot_type_to_sidc(cot_type: &str) -> Result<String, Error> {
+    Ok(Sidc::from_cot_type(cot_type)?.to_string())
+}
+
+/// Convert a 2525B/C SIDC to a CoT atom type.
+pub fn sidc_to_cot_type(sidc: &str) -> Result<String, Error> {
+    Ok(sidc.parse::<Sidc>()?.to_cot_type())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::base::Cot;
+    use crate::tak::detail::{MilSym, TakMarkerDetail};
+
+    use super::*;
+
+    #[test]
+    fn test_cot_type_sidc_round_trip() {
+        for (cot_type, sidc) in [
+            ("a-h-A-M-F-U-M", "SHAPMFUM-------"),
+            ("a-f-G-U-C-I", "SFGPUCI--------"),
+            ("a-u-G", "SUGP-----------"),
+            ("a-n-S-C-L-C-C", "SNSPCLCC-------"),
+            ("a-j-U", "SJUP-----------"),
+            ("a-f-X", "SFZP-----------"),
+        ] {
+            assert_eq!(cot_type_to_sidc(cot_type).unwrap(), sidc);
+            assert_eq!(sidc_to_cot_type(sidc).unwrap(), cot_type);
+        }
+        // Exercise affiliations, lowercase and modifiers
+        assert_eq!(sidc_to_cot_type("sdgpucr---aa**x").unwrap(), "a-f-G-U-C-R");
+        assert!(cot_type_to_sidc("b-m-p-s-m").is_err());
+        assert!(cot_type_to_sidc("a-q-G").is_err());
+        assert!(sidc_to_cot_type("GFGPGLB--------").is_err());
+        for status in ['C', 'D', 'X', 'F'] {
+            let sidc = format!("SFG{}UCI--------", status);
+            assert_eq!(sidc_to_cot_type(&sidc).unwrap(), "a-f-G-U-C-I", "{}", sidc);
+            assert!(!sidc.parse::<Sidc>().unwrap().planned);
+        }
+        assert!(sidc_to_cot_type("SFGQUCI--------").is_err());
+    }
+
+    #[test]
+    fn test_2525d() {
+        let sidc = Sidc::from_cot_type("a-h-G-E-V-A").unwrap();
+        assert_eq!(sidc.to_2525d().unwrap(), "10061500000000000000");
+        let sidc = Sidc::from_2525d("10031000001211000000").unwrap();
+        assert_eq!(sidc.to_cot_type(), "a-f-G");
+        let sidc = Sidc::from_2525d("10160100000000000000").unwrap();
+        assert_eq!(sidc.affiliation, Affiliation::Faker);
+        assert_eq!(sidc.dimension, BattleDimension::Air);
+        assert!(Sidc::from_2525d("1003").is_err());
+    }
+
+    #[test]
+    fn test_milsym_detail() {
+        let mut cot = Cot::<TakMarkerDetail> {
+            cot_type: "a-h-G-U-C-I".to_string(),
+            ..Default::default()
+        };
+        let sidc = Sidc::from_cot_type(&cot.cot_type).unwrap();
+        cot.detail.milsym = Some(MilSym::from(&sidc));
+        let xml = quick_xml::se::to_string(&cot).unwrap();
+        assert!(xml.contains(r#"<__milsym id="SHGPUCI--------"/>"#));
+        let cot1: Cot<TakMarkerDetail> = quick_xml::de::from_str(&xml).unwrap();
+        let sidc1 = cot1.detail.milsym.unwrap().sidc().unwrap();
+        assert_eq!(sidc1.to_cot_type(), cot.cot_type);
+    }
+}
@@ -1,6 +1,11 @@
+pub mod chat;
 pub mod create;
 pub mod detail;
 pub mod detect;
+pub mod geofence;
+#[cfg(feature = "iconset")]
+pub mod iconset;
+pub mod milsym;
 
 #[cfg(test)]
 mod test {
@@ -0,0 +1,447 @@
+//! CoT timestamps which remember how they were written.
+//!
+//! [`Timestamp`] is used for the `time`, `start` and `stale` attributes of [`Cot`](crate::base::Cot)
+//! and other timestamps in detail elements. A parsed timestamp serializes to the text it was
+//! parsed from, so relaying an event doesn't change its bytes. After [`Timestamp::with_precision()`]
+//! or adding a [`Duration`], it keeps its date/time separator and zone suffix, e.g. `z`, `-00:00`
+//! or `+0200`. Timestamps created from a `DateTime<Utc>` are written
+//! with millisecond precision, e.g. `2023-10-24T03:16:14.897Z`.
+//!
+//! By default, timestamps must be RFC 3339. [`TimeParsing::Lenient`] also accepts timestamps
+//! without a zone (taken as UTC), offsets without a colon, any number of fractional digits, and
+//! stray whitespace, e.g. `2023-10-24 03:16:14.985238` or `2023-10- 24T03:16:14+0200`. Use
+//! [`base::from_str_with()`](crate::base::from_str_with) or [`with_parsing()`] to select it for
+//! a call, or [`deserialize_lenient()`] for a field.
+
+use std::cell::Cell;
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::{Add, Deref, Sub};
+use std::str::FromStr;
+
+use chrono::{DateTime, Duration, FixedOffset, NaiveDateTime, Timelike, Utc};
+use serde::de::Error as DeError;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::Error;
+
+/// How strictly to parse timestamps.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TimeParsing {
+    /// RFC 3339 only, e.g. `2023-10-24T03:16:14.985238Z` or `2023-10-24T05:16:14+02:00`.
+    #[default]
+    Strict,
+    /// Also accept a missing zone, `+hhmm` or `+hh` offsets, more than 9 fractional digits and
+    /// stray whitespace.
+    Lenient,
+}
+
+thread_local! {
+    static TIME_PARSING: Cell<TimeParsing> = const { Cell::new(TimeParsing::Strict) };
+}
+
+/// Run `f` with timestamps deserialized on this thread parsed according to `mode`.
+///
+/// ```rust
+/// # use cot_proto::base::{from_str, CotBase, COT_BASE_EXAMPLE};
+/// # use cot_proto::time::{with_parsing, TimeParsing};
+/// let input = COT_BASE_EXAMPLE.replace("38.07Z", "38.07");
+/// assert!(from_str::<CotBase>(&input).is_err());
+/// let cot: CotBase = with_parsing(TimeParsing::Lenient, || from_str(&input)).unwrap();
+/// assert_eq!(cot.time.to_string(), "2005-04-05T11:43:38.07");
+/// ```
+pub fn with_parsing<R>(mode: TimeParsing, f: impl FnOnce() -> R) -> R {
+    struct Restore(TimeParsing);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            TIME_PARSING.with(|m| m.set(self.0));
+        }
+    }
+    let _restore = Restore(TIME_PARSING.with(|m| m.replace(mode)));
+    f()
+}
+
+/// The zone suffix of a timestamp, as written.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Zone {
+    /// `Z` or `z`
+    Utc(char),
+    /// Seconds east of UTC, and the suffix: `+hh:mm` or `-hh:mm`, or `+hhmm` or `+hh` (lenient
+    /// only).
+    Offset(i32, Box<str>),
+    /// No zone, taken as UTC.
+    None,
+}
+
+impl Zone {
+    /// Seconds east of UTC.
+    fn offset(&self) -> i32 {
+        match self {
+            Zone::Offset(offset, _) => *offset,
+            Zone::Utc(_) | Zone::None => 0,
+        }
+    }
+}
+
+/// A point in time, and how to write it.
+///
+/// Dereferences to `DateTime<Utc>`. Comparisons and hashing only consider the point in time, not
+/// how it is written.
+///
+/// Deserializing reads the parsing mode from thread-local state, set by [`with_parsing()`]
+/// around a call on the same thread; it is strict otherwise, including on other threads or
+/// executors the deserializer runs on. To fix the mode per field instead, use
+/// `#[serde(deserialize_with = "cot_proto::time::deserialize_lenient")]`.
+#[derive(Clone, Debug)]
+pub struct Timestamp {
+    time: DateTime<Utc>,
+    /// Number of fractional second digits, up to 9.
+    precision: u8,
+    separator: char,
+    zone: Zone,
+    /// The text parsed, if it isn't written back from the fields above, e.g. a leap second, or
+    /// whitespace or more than 9 fractional digits when parsed leniently.
+    text: Option<Box<str>>,
+}
+
+impl Timestamp {
+    /// The current time, written with millisecond precision.
+    pub fn now() -> Self {
+        Utc::now().into()
+    }
+
+    /// Parse a timestamp, e.g. from an attribute value.
+    pub fn parse(s: &str, mode: TimeParsing) -> Result<Self, Error> {
+        let text = s;
+        let invalid = |e: &dyn fmt::Display| {
+            Error::BadField(format!("invalid timestamp {:?}: {}", s, e).into())
+        };
+        let compact: String;
+        let s = match mode {
+            TimeParsing::Strict => s,
+            TimeParsing::Lenient => {
+                compact = s.chars().filter(|c| !c.is_whitespace()).collect();
+                &compact
+            }
+        };
+        // Only the date and time fields are ASCII in a valid timestamp. Lenient timestamps may
+        // have had a space between them removed.
+        let min_len = if mode == TimeParsing::Lenient { 18 } else { 19 };
+        if s.len() < min_len || !s.is_char_boundary(10) || !s.is_char_boundary(min_len) {
+            return Err(invalid(&"too short"));
+        }
+        let (date, rest) = s.split_at(10);
+        let (separator, time) = match rest.chars().next() {
+            Some(sep @ ('T' | 't' | ' ')) => (sep, &rest[1..]),
+            // Whitespace separating date and time was removed above.
+            Some('0'..='9') if mode == TimeParsing::Lenient => (' ', rest),
+            _ => return Err(invalid(&"expected 'T' between date and time")),
+        };
+
+        let (time, zone) = if let Some(utc @ ('Z' | 'z')) = time.chars().last() {
+            (&time[..time.len() - 1], Zone::Utc(utc))
+        } else if let Some(i) = time.rfind(['+', '-']) {
+            let offset =
+                parse_offset(&time[i..], mode).ok_or_else(|| invalid(&"invalid offset"))?;
+            (&time[..i], Zone::Offset(offset, time[i..].into()))
+        } else if mode == TimeParsing::Lenient {
+            (time, Zone::None)
+        } else {
+            return Err(invalid(&"missing zone"));
+        };
+
+        let (seconds, fraction) = time.split_once('.').unwrap_or((time, ""));
+        if (fraction.is_empty() && time.ends_with('.'))
+            || !fraction.bytes().all(|b| b.is_ascii_digit())
+            || (fraction.len() > 9 && mode == TimeParsing::Strict)
+        {
+            return Err(invalid(&"invalid fractional seconds"));
+        }
+        // chrono allows padding with spaces, e.g. `2023-10-24T 3:16:14`.
+        let fields = |s: &str, sep| s.bytes().all(|b| b.is_ascii_digit() || b == sep);
+        if !fields(date, b'-') || seconds.len() != 8 || !fields(seconds, b':') {
+            return Err(invalid(&"expected YYYY-MM-DD and hh:mm:ss"));
+        }
+        let fraction = &fraction[..fraction.len().min(9)];
+        let naive =
+            NaiveDateTime::parse_from_str(&format!("{}T{}", date, seconds), "%Y-%m-%dT%H:%M:%S")
+                .map_err(|e| invalid(&e))?;
+        let nanos = format!("{:0<9}", fraction)
+            .parse::<u32>()
+            .unwrap_or_default();
+        // A leap second (`:60`) is parsed as `:59` with 1e9 nanoseconds.
+        let naive = naive
+            .with_nanosecond(naive.nanosecond() + nanos)
+            .ok_or_else(|| invalid(&"invalid fractional seconds"))?;
+        let mut timestamp = Self {
+            time: naive.and_utc() - Duration::seconds(zone.offset().into()),
+            precision: fraction.len() as u8,
+            separator,
+            zone,
+            text: None,
+        };
+        if timestamp.to_string() != text {
+            timestamp.text = Some(text.into());
+        }
+        Ok(timestamp)
+    }
+
+    /// Number of fractional second digits this timestamp is written with.
+    pub fn precision(&self) -> u8 {
+        self.precision
+    }
+
+    /// Write this timestamp with `digits` fractional second digits, up to 9.
+    pub fn with_precision(self, digits: u8) -> Self {
+        Self {
+            precision: digits.min(9),
+            text: None,
+            ..self
+        }
+    }
+}
+
+/// Seconds east of UTC for `+hh:mm`, `+hhmm` or `+hh` (lenient only).
+fn parse_offset(s: &str, mode: TimeParsing) -> Option<i32> {
+    if !s.is_ascii() {
+        return None;
+    }
+    let sign = if s.starts_with('-') { -1 } else { 1 };
+    let digits = &s[1..];
+    let (hours, minutes) = match (digits.len(), mode) {
+        (5, _) if digits.as_bytes()[2] == b':' => (&digits[..2], &digits[3..]),
+        (4, TimeParsing::Lenient) => (&digits[..2], &digits[2..]),
+        (2, TimeParsing::Lenient) => (digits, "00"),
+        _ => return None,
+    };
+    if !(hours.bytes().chain(minutes.bytes())).all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let (hours, minutes): (i32, i32) = (hours.parse().ok()?, minutes.parse().ok()?);
+    (hours < 24 && minutes < 60).then_some(sign * (hours * 3600 + minutes * 60))
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(text) = &self.text {
+            return f.write_str(text);
+        }
+        let offset = FixedOffset::east_opt(self.zone.offset()).expect("offset is less than a day");
+        let local = self.time.with_timezone(&offset);
+        write!(f, "{}", local.format("%Y-%m-%d"))?;
+        write!(f, "{}{}", self.separator, local.format("%H:%M:%S"))?;
+        if self.precision > 0 {
+            // Leap seconds have nanoseconds past 1e9.
+            let nanos = format!("{:09}", local.nanosecond() % 1_000_000_000);
+            write!(f, ".{}", &nanos[..self.precision as usize])?;
+        }
+        match &self.zone {
+            Zone::Utc(utc) => write!(f, "{}", utc),
+            Zone::Offset(_, suffix) => f.write_str(suffix),
+            Zone::None => Ok(()),
+        }
+    }
+}
+
+impl FromStr for Timestamp {
+    type Err = Error;
+
+    /// Parse an RFC 3339 timestamp.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s, TimeParsing::Strict)
+    }
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Self::parse(&s, TIME_PARSING.with(Cell::get)).map_err(DeError::custom)
+    }
+}
+
+/// Deserialize a timestamp with [`TimeParsing::Lenient`], whatever the [`with_parsing()`] mode.
+///
+/// ```rust
+/// # use cot_proto::time::Timestamp;
+/// #[derive(serde::Deserialize)]
+/// struct Sighting {
+///     #[serde(rename = "@time", deserialize_with = "cot_proto::time::deserialize_lenient")]
+///     time: Timestamp,
+/// }
+/// let s: Sighting = quick_xml::de::from_str(r#"<sighting time="2023-10-24 03:16:14"/>"#).unwrap();
+/// assert_eq!(s.time.to_string(), "2023-10-24 03:16:14");
+/// ```
+pub fn deserialize_lenient<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Timestamp, D::Error> {
+    let s = String::deserialize(deserializer)?;
+    Timestamp::parse(&s, TimeParsing::Lenient).map_err(DeError::custom)
+}
+
+impl From<DateTime<Utc>> for Timestamp {
+    fn from(time: DateTime<Utc>) -> Self {
+        Self {
+            time,
+            precision: 3,
+            separator: 'T',
+            zone: Zone::Utc('Z'),
+            text: None,
+        }
+    }
+}
+
+impl From<Timestamp> for DateTime<Utc> {
+    fn from(timestamp: Timestamp) -> Self {
+        timestamp.time
+    }
+}
+
+impl Deref for Timestamp {
+    type Target = DateTime<Utc>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.time
+    }
+}
+
+impl PartialEq for Timestamp {
+    fn eq(&self, other: &Self) -> bool {
+        self.time == other.time
+    }
+}
+
+impl Eq for Timestamp {}
+
+impl PartialEq<DateTime<Utc>> for Timestamp {
+    fn eq(&self, other: &DateTime<Utc>) -> bool {
+        self.time == *other
+    }
+}
+
+impl PartialOrd for Timestamp {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Timestamp {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.time.cmp(&other.time)
+    }
+}
+
+impl Hash for Timestamp {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.time.hash(state)
+    }
+}
+
+impl Add<Duration> for Timestamp {
+    type Output = Timestamp;
+
+    fn add(self, rhs: Duration) -> Self::Output {
+        Self {
+            time: self.time + rhs,
+            text: None,
+            ..self
+        }
+    }
+}
+
+impl Sub<Duration> for Timestamp {
+    type Output = Timestamp;
+
+    fn sub(self, rhs: Duration) -> Self::Output {
+        Self {
+            time: self.time - rhs,
+            text: None,
+            ..self
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_timestamp_round_trip() {
+        for s in [
+            "2005-04-05T11:43:38.07Z",
+            "2023-10-24T03:16:14.897441Z",
+            "2023-10-24T03:16:14Z",
+            "2023-10-24T03:16:14.123456789Z",
+            "2023-10-24T05:16:14.5+02:00",
+            "2023-10-23T22:16:14-05:00",
+            "2023-10-24 03:16:14.985Z",
+            "2023-10-24t03:16:14.985z",
+            "2023-10-24T03:16:14-00:00",
+            "2016-12-31T23:59:60Z",
+            "2016-12-31T23:59:60.5Z",
+        ] {
+            let timestamp: Timestamp = s.parse().unwrap();
+            assert_eq!(timestamp.to_string(), s);
+        }
+        let t: Timestamp = "2023-10-24T05:16:14.5+02:00".parse().unwrap();
+        assert_eq!(t, "2023-10-24T03:16:14.500Z".parse::<Timestamp>().unwrap());
+        assert_eq!(t.precision(), 1);
+        assert_eq!(
+            (t.clone() + Duration::minutes(1)).to_string(),
+            "2023-10-24T05:17:14.5+02:00"
+        );
+        assert_eq!(Timestamp::from(*t).to_string(), "2023-10-24T03:16:14.500Z");
+        let leap: Timestamp = "2016-12-31T23:59:60Z".parse().unwrap();
+        assert!(leap > "2016-12-31T23:59:59.999Z".parse::<Timestamp>().unwrap());
+        assert!(leap < "2017-01-01T00:00:00Z".parse::<Timestamp>().unwrap());
+    }
+
+    #[test]
+    fn test_timestamp_lenient() {
+        for s in [
+            "2023-10-24T03:16:14.985238",
+            "2023-10-24 03:16:14.985238",
+            "2023-10-24 03:16:14",
+            "2023-10-24T03:16:14+0200",
+            "2023-10-24T03:16:14+02",
+            "2023-10-24T03:16:14.1234567891Z",
+            "2023-10- 24T03:16:14.985238Z",
+            "2023-10-24  03:16:14.985238z",
+        ] {
+            assert!(Timestamp::parse(s, TimeParsing::Strict).is_err(), "{}", s);
+            let t = Timestamp::parse(s, TimeParsing::Lenient).unwrap();
+            assert_eq!(t.to_string(), s);
+        }
+        let t = Timestamp::parse("2023-10-24 03:16:14.985238", TimeParsing::Lenient).unwrap();
+        assert_eq!(
+            t,
+            "2023-10-24T03:16:14.985238Z".parse::<Timestamp>().unwrap()
+        );
+        let t = Timestamp::parse("2023-10-24T05:16:14+0200", TimeParsing::Lenient).unwrap();
+        assert_eq!(t, "2023-10-24T03:16:14Z".parse::<Timestamp>().unwrap());
+        assert_eq!(
+            (t + Duration::hours(1)).to_string(),
+            "2023-10-24T06:16:14+0200"
+        );
+        let t =
+            Timestamp::parse("2023-10-24  03:16:14.1234567891 z", TimeParsing::Lenient).unwrap();
+        assert_eq!(t.to_string(), "2023-10-24  03:16:14.1234567891 z");
+        assert_eq!(t.precision(), 9);
+        assert_eq!(t.with_precision(3).to_string(), "2023-10-24 03:16:14.123z");
+
+        for s in [
+            "2023-10-24",
+            "2023-10-24T03:16:14.Z",
+            "2023-10-24X03:16:14Z",
+            "24/10/2023 03:16:14",
+        ] {
+            assert!(Timestamp::parse(s, TimeParsing::Lenient).is_err(), "{}", s);
+        }
+    }
+}
@@ -0,0 +1,315 @@
+//! Configurable XML output for CoT messages.
+//!
+//! `quick_xml::se::to_string()` writes [`Cot`] fields in declaration order, so `<detail>` comes
+//! before `<point>`, with double quotes and no XML declaration. Some receivers require the schema
+//! order, and ATAK itself writes a declaration and single quotes. [`CotWriter`] writes either
+//! style:
+//!
+//! ```rust
+//! # use cot_proto::base::{from_str, CotBase};
+//! # use cot_proto::examples::COT_TRACK_EXAMPLE;
+//! # use cot_proto::writer::CotWriter;
+//! let cot: CotBase = from_str(COT_TRACK_EXAMPLE).unwrap();
+//! let xml = CotWriter::atak().to_string(&cot).unwrap();
+//! assert!(xml.starts_with("<?xml version='1.0' encoding='UTF-8' standalone='yes'?>"));
+//! assert!(xml.contains("<event version='2.0'"));
+//! assert!(xml.find("<point").unwrap() < xml.find("<detail").unwrap());
+//! ```
+
+use std::io;
+
+use quick_xml::events::{BytesDecl, BytesStart, Event};
+use quick_xml::{Reader, Writer};
+use serde::Serialize;
+
+use crate::base::Cot;
+use crate::Error;
+
+/// Quote character for attribute values.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Quote {
+    #[default]
+    Double,
+    Single,
+}
+
+impl Quote {
+    fn char(self) -> char {
+        match self {
+            Quote::Double => '"',
+            Quote::Single => '\'',
+        }
+    }
+}
+
+/// Writes CoT messages as XML, with options for element order, quotes, the XML declaration and
+/// indentation. By default, writes `<point>` before `<detail>` as in the CoT schema, with double
+/// quotes, no declaration, and no indentation.
+#[derive(Clone, Debug)]
+pub struct CotWriter {
+    point_first: bool,
+    quote: Quote,
+    declaration: bool,
+    indent: Option<(u8, usize)>,
+}
+
+impl Default for CotWriter {
+    fn default() -> Self {
+        Self {
+            point_first: true,
+            quote: Quote::Double,
+            declaration: false,
+            indent: None,
+        }
+    }
+}
+
+impl CotWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The style of ATAK: point first, single quotes, and an XML declaration.
+    pub fn atak() -> Self {
+        Self::new().with_quote(Quote::Single).with_declaration(true)
+    }
+
+    /// Write `<point>` before `<detail>`, as in the CoT schema, or after, in [`Cot`] field order.
+    pub fn with_point_first(mut self, point_first: bool) -> Self {
+        self.point_first = point_first;
+        self
+    }
+
+    /// Quote attribute values, and those of the declaration, with `quote`.
+    pub fn with_quote(mut self, quote: Quote) -> Self {
+        self.quote = quote;
+        self
+    }
+
+    /// Start with the XML declaration `<?xml version="1.0" encoding="UTF-8" standalone="yes"?>`.
+    pub fn with_declaration(mut self, declaration: bool) -> Self {
+        self.declaration = declaration;
+        self
+    }
+
+    /// Put each element on its own line, indented by `size` `indent_char`s per level.
+    pub fn with_indent(mut self, indent_char: u8, size: usize) -> Self {
+        self.indent = Some((indent_char, size));
+        self
+    }
+
+    /// Write `cot` to `writer`.
+    pub fn write<D: Serialize, W: io::Write>(&self, cot: &Cot<D>, writer: W) -> Result<(), Error> {
+        let xml = quick_xml::se::to_string(cot)?;
+        let mut events = vec![];
+        let mut reader = Reader::from_str(&xml);
+        loop {
+            match reader.read_event()? {
+                Event::Eof => break,
+                event => events.push(event.into_owned()),
+            }
+        }
+        if self.point_first {
+            move_point_first(&mut events);
+        }
+
+        let mut writer = match self.indent {
+            Some((indent_char, size)) => Writer::new_with_indent(writer, indent_char, size),
+            None => Writer::new(writer),
+        };
+        if self.declaration {
+            let q = self.quote.char();
+            let content = format!(
+                "xml version={q}1.0{q} encoding={q}UTF-8{q} standalone={q}yes{q}",
+                q = q
+            );
+            writer.write_event(Event::Decl(BytesDecl::from_start(
+                BytesStart::from_content(content, 3),
+            )))?;
+        }
+        for event in events {
+            let event = match event {
+                Event::Start(start) => Event::Start(self.requote(&start)?),
+                Event::Empty(start) => Event::Empty(self.requote(&start)?),
+                event => event,
+            };
+            writer.write_event(event)?;
+        }
+        Ok(())
+    }
+
+    /// Write `cot` to a string.
+    pub fn to_string<D: Serialize>(&self, cot: &Cot<D>) -> Result<String, Error> {
+        let mut buf = vec![];
+        self.write(cot, &mut buf)?;
+        Ok(String::from_utf8(buf).expect("XML is written as UTF-8"))
+    }
+
+    /// The tag `start` with attribute values in the configured quotes.
+    fn requote(&self, start: &BytesStart) -> Result<BytesStart<'static>, Error> {
+        let name = String::from_utf8_lossy(start.name().as_ref()).to_string();
+        let mut content = name.clone();
+        let q = self.quote.char();
+        for attr in start.attributes() {
+            let attr = attr?;
+            let value = attr.unescape_value()?;
+            content += &format!(" {}={}", String::from_utf8_lossy(attr.key.as_ref()), q);
+            for c in value.chars() {
+                match c {
+                    '&' => content += "&amp;",
+                    '<' => content += "&lt;",
+                    '>' => content += "&gt;",
+                    '"' if q == '"' => content += "&quot;",
+                    '\'' if q == '\'' => content += "&apos;",
+                    c => content.push(c),
+                }
+            }
+            content.push(q);
+        }
+        Ok(BytesStart::from_content(content, name.len()))
+    }
+}
+
+/// Move the top-level `<point>` element of the event before `<detail>`.
+fn move_point_first(events: &mut Vec<Event<'static>>) {
+    // Ranges of events of the event's child elements `point` and `detail`.
+    let mut point = None;
+    let mut detail = None;
+    let mut depth = 0;
+    let mut child_start = 0;
+    for (i, event) in events.iter().enumerate() {
+        let (name, end) = match event {
+            Event::Start(_) => {
+                depth += 1;
+                if depth == 2 {
+                    child_start = i;
+                }
+                continue;
+            }
+            Event::Empty(start) if depth == 1 => (start.name().as_ref().to_vec(), i),
+            Event::End(end) => {
+                depth -= 1;
+                if depth != 1 {
+                    continue;
+                }
+                (end.name().as_ref().to_vec(), i)
+            }
+            _ => continue,
+        };
+        let start = if matches!(event, Event::Empty(_)) {
+            i
+        } else {
+            child_start
+        };
+        match name.as_slice() {
+            b"point" => point = Some(start..end + 1),
+            b"detail" => detail = Some(start..end + 1),
+            _ => {}
+        }
+    }
+    if let (Some(point), Some(detail)) = (point, detail) {
+        if detail.start < point.start {
+            let point_events: Vec<_> = events.drain(point).collect();
+            events.splice(detail.start..detail.start, point_events);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::base::{from_str, CotBase, COT_BASE_EXAMPLE};
+    use crate::detail::parse;
+    use crate::examples::COT_TRACK_EXAMPLE;
+
+    #[test]
+    fn test_writer_options() {
+        let cot: CotBase = from_str(COT_BASE_EXAMPLE).unwrap();
+        let attributes = r#"version="2.0" uid="J-01334" type="a-h-A-M-F-U-M" time="2005-04-05T11:43:38.07Z" start="2005-04-05T11:43:38.07Z" stale="2005-04-05T11:45:38.07Z""#;
+        let point =
+            r#"<point lat="30.0090027" lon="-85.9578735" ce="45.3" hae="-42.6" le="99.5"/>"#;
+
+        let xml = CotWriter::new().to_string(&cot).unwrap();
+        assert_eq!(
+            xml,
+            format!("<event {}>{}<detail/></event>", attributes, point)
+        );
+        let xml = CotWriter::new()
+            .with_point_first(false)
+            .to_string(&cot)
+            .unwrap();
+        assert_eq!(xml, quick_xml::se::to_string(&cot).unwrap());
+
+        let xml = CotWriter::atak()
+            .with_indent(b' ', 2)
+            .to_string(&cot)
+            .unwrap();
+        let expected = format!(
+            "<?xml version='1.0' encoding='UTF-8' standalone='yes'?>\n<event {}>\n  {}\n  <detail/>\n</event>",
+            attributes, point
+        )
+        .replace('"', "'");
+        assert_eq!(xml, expected);
+        assert_eq!(from_str::<CotBase>(&xml).unwrap(), cot);
+    }
+
+    #[test]
+    fn test_writer_detail() {
+        #[derive(Serialize)]
+        struct Detail {
+            contact: Contact,
+            remarks: Remarks,
+        }
+        #[derive(Serialize)]
+        struct Contact {
+            #[serde(rename = "@callsign")]
+            callsign: String,
+        }
+        #[derive(Serialize)]
+        struct Remarks {
+            #[serde(rename = "@source")]
+            source: String,
+            #[serde(rename = "$text")]
+            text: String,
+        }
+
+        let base: CotBase = from_str(COT_TRACK_EXAMPLE).unwrap();
+        let cot = Cot {
+            version: base.version,
+            uid: base.uid,
+            cot_type: base.cot_type,
+            time: base.time,
+            start: base.start,
+            stale: base.stale,
+            how: base.how,
+            detail: Detail {
+                contact: Contact {
+                    callsign: "R&B 1".into(),
+                },
+                remarks: Remarks {
+                    source: r#"it's "here""#.into(),
+                    text: "a < b".into(),
+                },
+            },
+            point: base.point,
+        };
+
+        let mut buf = vec![];
+        CotWriter::atak()
+            .with_indent(b'\t', 1)
+            .write(&cot, &mut buf)
+            .unwrap();
+        let xml = String::from_utf8(buf).unwrap();
+        assert!(xml.ends_with(
+            r#"<point lat='-23.14187321890312' lon='126.87965999741635' ce='10' hae='1321.2999' le='10'/>
+	<detail>
+		<contact callsign='R&amp;B 1'/>
+		<remarks source='it&apos;s "here"'>a &lt; b</remarks>
+	</detail>
+</event>"#
+        ));
+        let parsed = parse(&xml).unwrap();
+        assert_eq!(parsed.point, cot.point);
+        assert_eq!(parsed.detail.len(), 2);
+    }
+}